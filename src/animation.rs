@@ -0,0 +1,61 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::window;
+use yew::Callback;
+
+// Milliseconds on the same clock as animation frames and event timestamps
+pub fn now() -> f64 {
+    window()
+        .and_then(|window| window.performance())
+        .map(|performance| performance.now())
+        .unwrap_or_default()
+}
+
+type FrameClosure = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+// Emits the current time in milliseconds once per animation frame until dropped
+pub struct AnimationLoop {
+    handle: Rc<Cell<i32>>,
+    closure: FrameClosure,
+}
+
+fn request_frame(closure: &FrameClosure) -> i32 {
+    window()
+        .unwrap()
+        .request_animation_frame(closure.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+        .expect("Couldn't request animation frame.")
+}
+
+impl AnimationLoop {
+    pub fn new(callback: Callback<f64>) -> Self {
+        let handle = Rc::new(Cell::new(0));
+        let closure: FrameClosure = Rc::new(RefCell::new(None));
+
+        *closure.borrow_mut() = Some(Closure::wrap(Box::new({
+            let handle = handle.clone();
+            let closure = closure.clone();
+            move || {
+                handle.set(request_frame(&closure));
+
+                callback.emit(now())
+            }
+        }) as Box<dyn FnMut()>));
+
+        handle.set(request_frame(&closure));
+
+        Self { handle, closure }
+    }
+}
+
+impl Drop for AnimationLoop {
+    fn drop(&mut self) {
+        if let Some(window) = window() {
+            let _ = window.cancel_animation_frame(self.handle.get());
+        }
+        // The closure holds a reference to itself, break the cycle so it can be freed
+        self.closure.borrow_mut().take();
+    }
+}
@@ -0,0 +1,249 @@
+use nalgebra::{UnitQuaternion, Vector3};
+use nalgebra_glm::Mat4x4;
+
+// Fraction of the spin velocity kept after one second
+const DAMPING_PER_SECOND: f32 = 0.05;
+// Spin slower than this, in radians per second, comes to a stop
+const MIN_SPIN_SPEED: f32 = 0.01;
+// A drag released after holding still this long doesn't spin
+const RELEASE_WINDOW_MS: f64 = 80.0;
+// Quaternion products slowly lose unit length, so the orientation is renormalized this often
+const RENORMALIZE_EVERY: u32 = 64;
+const TWEEN_SECONDS: f32 = 0.6;
+
+struct Drag {
+    last_point: Vector3<f32>,
+    last_time: f64,
+}
+
+struct Tween {
+    from: UnitQuaternion<f32>,
+    to: UnitQuaternion<f32>,
+    progress: f32,
+}
+
+pub struct Arcball {
+    orientation: UnitQuaternion<f32>,
+    drag: Option<Drag>,
+    tween: Option<Tween>,
+    // Rotation axis scaled by angular speed in radians per second
+    velocity: Vector3<f32>,
+    // Rotations applied since the orientation was last renormalized
+    updates: u32,
+}
+
+// Pointer position relative to the center of the viewport, in radii of the ball
+pub fn normalized_point(x: f32, y: f32, width: f32, height: f32) -> (f32, f32) {
+    let radius = (width.min(height) / 2.0).max(1.0);
+
+    ((x - width / 2.0) / radius, (height / 2.0 - y) / radius)
+}
+
+// Projects a normalized pointer position onto the ball, points outside land on its rim
+fn sphere_point((x, y): (f32, f32)) -> Vector3<f32> {
+    let length_squared = x * x + y * y;
+
+    if length_squared <= 1.0 {
+        Vector3::new(x, y, (1.0 - length_squared).sqrt())
+    } else {
+        Vector3::new(x, y, 0.0).normalize()
+    }
+}
+
+impl Arcball {
+    pub fn new() -> Self {
+        Self {
+            orientation: UnitQuaternion::identity(),
+            drag: None,
+            tween: None,
+            velocity: Vector3::zeros(),
+            updates: 0,
+        }
+    }
+
+    pub fn model_matrix(&self) -> Mat4x4 {
+        self.orientation.to_homogeneous()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    // Turns the model to the target over the next frames instead of jumping
+    pub fn animate_to(&mut self, target: UnitQuaternion<f32>) {
+        self.velocity = Vector3::zeros();
+        self.tween = Some(Tween {
+            from: self.orientation,
+            to: target,
+            progress: 0.0,
+        });
+    }
+
+    pub fn begin_drag(&mut self, point: (f32, f32), now: f64) {
+        self.velocity = Vector3::zeros();
+        self.tween = None;
+        self.drag = Some(Drag {
+            last_point: sphere_point(point),
+            last_time: now,
+        });
+    }
+
+    // The ball is dragged in view space, view_rotation maps the drag back onto the model
+    pub fn drag(
+        &mut self,
+        point: (f32, f32),
+        now: f64,
+        view_rotation: &UnitQuaternion<f32>,
+        sensitivity: f32,
+    ) {
+        let drag = match &mut self.drag {
+            Some(drag) => drag,
+            None => return,
+        };
+
+        let point = sphere_point(point);
+        let delta = UnitQuaternion::rotation_between(&drag.last_point, &point)
+            .unwrap_or_else(UnitQuaternion::identity);
+        let delta = UnitQuaternion::from_scaled_axis(delta.scaled_axis() * sensitivity);
+        let delta = view_rotation.inverse() * delta * view_rotation;
+
+        let elapsed = ((now - drag.last_time) / 1000.0) as f32;
+        if elapsed > 0.0 {
+            // Smooth out uneven event timing
+            self.velocity = (self.velocity + delta.scaled_axis() / elapsed) / 2.0;
+        }
+
+        drag.last_point = point;
+        drag.last_time = now;
+
+        self.apply(delta);
+    }
+
+    // Stops dragging without letting the ball spin on
+    pub fn cancel_drag(&mut self) {
+        self.drag = None;
+        self.velocity = Vector3::zeros();
+    }
+
+    pub fn end_drag(&mut self, now: f64) {
+        if let Some(drag) = self.drag.take() {
+            if now - drag.last_time > RELEASE_WINDOW_MS {
+                self.velocity = Vector3::zeros();
+            }
+        }
+    }
+
+    // Advances a tween or the spin left after a release, returns whether the orientation changed
+    pub fn step(&mut self, elapsed: f32) -> bool {
+        if self.drag.is_some() {
+            return false;
+        }
+
+        if let Some(tween) = &mut self.tween {
+            tween.progress = (tween.progress + elapsed / TWEEN_SECONDS).min(1.0);
+
+            // Ease in and out
+            let t = tween.progress * tween.progress * (3.0 - 2.0 * tween.progress);
+            self.orientation = tween
+                .from
+                .try_slerp(&tween.to, t, f32::EPSILON)
+                .unwrap_or(tween.to);
+
+            if tween.progress >= 1.0 {
+                self.tween = None;
+            }
+
+            return true;
+        }
+
+        if self.velocity == Vector3::zeros() {
+            return false;
+        }
+
+        self.apply(UnitQuaternion::from_scaled_axis(self.velocity * elapsed));
+
+        self.velocity *= DAMPING_PER_SECOND.powf(elapsed);
+        if self.velocity.norm() < MIN_SPIN_SPEED {
+            self.velocity = Vector3::zeros();
+        }
+
+        true
+    }
+
+    fn apply(&mut self, rotation: UnitQuaternion<f32>) {
+        self.orientation = rotation * self.orientation;
+
+        self.updates += 1;
+        if self.updates == RENORMALIZE_EVERY {
+            self.updates = 0;
+            self.orientation.renormalize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Drags a quarter of the ball's radius to the right over 16 ms steps
+    fn flick(arcball: &mut Arcball, release_at: f64) {
+        arcball.begin_drag((0.0, 0.0), 0.0);
+        for step in 1..=4 {
+            let x = step as f32 / 16.0;
+            arcball.drag(
+                (x, 0.0),
+                step as f64 * 16.0,
+                &UnitQuaternion::identity(),
+                1.0,
+            );
+        }
+        arcball.end_drag(release_at);
+    }
+
+    #[test]
+    fn released_spin_slows_to_a_stop() {
+        let mut arcball = Arcball::new();
+        flick(&mut arcball, 64.0);
+
+        assert!(arcball.step(1.0 / 60.0));
+        let steps = (0..600).take_while(|_| arcball.step(1.0 / 60.0)).count();
+        assert!(steps < 600, "still spinning after ten seconds");
+    }
+
+    #[test]
+    fn holding_still_before_release_stops_the_spin() {
+        let mut arcball = Arcball::new();
+        flick(&mut arcball, 64.0 + RELEASE_WINDOW_MS + 1.0);
+
+        assert!(!arcball.step(1.0 / 60.0));
+    }
+
+    #[test]
+    fn animation_ends_at_the_target() {
+        let mut arcball = Arcball::new();
+        let target = UnitQuaternion::from_euler_angles(0.3, -1.2, 2.0);
+        arcball.animate_to(target);
+
+        while arcball.step(1.0 / 60.0) {}
+        assert!(arcball.orientation.angle_to(&target) < 1e-4);
+    }
+
+    #[test]
+    fn many_small_rotations_keep_a_unit_orientation() {
+        let rotation = UnitQuaternion::from_euler_angles(0.0011f32, 0.0007, 0.0003);
+
+        // Unchecked products drift away from unit length, so renormalizing has work to do
+        let mut drifted = UnitQuaternion::identity();
+        for _ in 0..10_000 {
+            drifted = rotation * drifted;
+        }
+        assert!((drifted.quaternion().norm() - 1.0).abs() > 5e-5);
+
+        let mut arcball = Arcball::new();
+        for _ in 0..10_000 {
+            arcball.apply(rotation);
+        }
+        let norm = arcball.orientation.quaternion().norm();
+        assert!((norm - 1.0).abs() < 1e-5, "norm drifted to {}", norm);
+    }
+}
@@ -1,4 +1,27 @@
-use nalgebra_glm::{Vec3, Mat4x4};
+use nalgebra::UnitQuaternion;
+use nalgebra_glm::{Mat4x4, Vec3};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    // Dragging rotates the cube model in front of a fixed camera
+    OrbitModel,
+    // The mouse turns the camera in place while the pointer is locked
+    FreeLook,
+    // Dragging moves the camera around its orbit target, the cube center unless panned away
+    OrbitCamera,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+const MIN_FOV: f32 = 15.0;
+const MAX_FOV: f32 = 120.0;
+// Half the visible height of orthographic views
+const MIN_ORTHO_SIZE: f32 = 0.1;
+const MAX_ORTHO_SIZE: f32 = 5.0;
 
 pub struct Camera {
     position: Vec3,
@@ -9,6 +32,10 @@ pub struct Camera {
     aspect: f32,
     near: f32,
     far: f32,
+    projection: Projection,
+    ortho_size: f32,
+    // What orbiting turns around, panning moves it along with the camera
+    target: Vec3,
 
     yaw: f32,
     pitch: f32,
@@ -29,6 +56,9 @@ impl Camera {
             aspect: 4.0 / 3.0,
             near: 0.1,
             far: 100.0,
+            projection: Projection::Perspective,
+            ortho_size: 1.0,
+            target: Vec3::zeros(),
             yaw: -90.0,
             pitch: 0.0,
         }
@@ -38,12 +68,81 @@ impl Camera {
         nalgebra_glm::look_at(&self.position, &(self.position + self.front), &self.up)
     }
 
+    // Rotation part of the view matrix, from world to view space
+    pub fn view_rotation(&self) -> UnitQuaternion<f32> {
+        let view = self.calculate_view_matrix();
+
+        UnitQuaternion::from_matrix(&view.fixed_slice::<3, 3>(0, 0).into_owned())
+    }
+
     pub fn calculate_projection_matrix(&self) -> Mat4x4 {
-        nalgebra_glm::perspective(self.aspect, self.fov, self.near, self.far)
+        match self.projection {
+            Projection::Perspective => {
+                nalgebra_glm::perspective(self.aspect, self.fov, self.near, self.far)
+            }
+            Projection::Orthographic => {
+                let (width, height) = (self.ortho_size * self.aspect, self.ortho_size);
+
+                nalgebra_glm::ortho(-width, width, -height, height, self.near, self.far)
+            }
+        }
+    }
+
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        // Keep the orbit target at about the same size on screen
+        if projection == Projection::Orthographic && self.projection == Projection::Perspective {
+            let distance = nalgebra_glm::distance(&self.position, &self.target).max(self.near);
+            self.ortho_size =
+                (distance * (self.fov / 2.0).tan()).clamp(MIN_ORTHO_SIZE, MAX_ORTHO_SIZE);
+        }
+
+        self.projection = projection;
+    }
+
+    // Narrows the view for factors below 1 and widens it above
+    pub fn zoom(&mut self, factor: f32) {
+        match self.projection {
+            Projection::Perspective => {
+                let fov = (self.fov.to_degrees() * factor).clamp(MIN_FOV, MAX_FOV);
+                self.fov = fov.to_radians();
+            }
+            Projection::Orthographic => {
+                self.ortho_size = (self.ortho_size * factor).clamp(MIN_ORTHO_SIZE, MAX_ORTHO_SIZE);
+            }
+        }
+    }
+
+    // Slides the camera and its orbit target sideways so the scene follows a drag of dx, dy pixels
+    // on a viewport this tall
+    pub fn pan(&mut self, dx: f32, dy: f32, viewport_height: f32) {
+        let visible_height = match self.projection {
+            Projection::Perspective => {
+                let distance = nalgebra_glm::distance(&self.position, &self.target);
+                2.0 * distance.max(self.near) * (self.fov / 2.0).tan()
+            }
+            Projection::Orthographic => 2.0 * self.ortho_size,
+        };
+        let scale = visible_height / viewport_height.max(1.0);
+
+        let offset = self.right * -dx * scale + self.up * dy * scale;
+        self.position += offset;
+        self.target += offset;
     }
 
-    pub fn move_dir(&mut self, direction: Vec3) {       
-        let velocity = direction.normalize() * 0.05;
+    pub fn move_dir(&mut self, direction: Vec3, distance: f32) {
+        if direction == Vec3::zeros() {
+            return;
+        }
+
+        let velocity = direction.normalize() * distance;
 
         self.position += velocity.x * self.right;
         self.position += velocity.y * self.up;
@@ -54,6 +153,38 @@ impl Camera {
         self.yaw += dx;
         self.pitch += dy;
 
+        self.update_vectors();
+    }
+
+    // Turns the camera in place to face the target
+    pub fn look_at(&mut self, target: Vec3) {
+        let direction = target - self.position;
+        if direction == Vec3::zeros() {
+            return;
+        }
+
+        let direction = direction.normalize();
+        self.yaw = direction.z.atan2(direction.x).to_degrees();
+        self.pitch = direction.y.asin().to_degrees();
+
+        self.update_vectors();
+    }
+
+    // Turns the camera in place to face its orbit target
+    pub fn look_at_target(&mut self) {
+        self.look_at(self.target);
+    }
+
+    // Moves the camera around its orbit target at its current distance, keeping it in view
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        let distance = nalgebra_glm::distance(&self.position, &self.target);
+
+        self.look_at_target();
+        self.rotate(dx, dy);
+        self.position = self.target - self.front * distance;
+    }
+
+    fn update_vectors(&mut self) {
         if self.pitch > 89.0 {
             self.pitch = 89.0;
         }
@@ -73,3 +204,24 @@ impl Camera {
         self.up = nalgebra_glm::cross(&self.right, &self.front).normalize();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orbiting_keeps_the_panned_target() {
+        let mut camera = Camera::new();
+        camera.pan(-100.0, 50.0, 400.0);
+        let target = camera.target;
+        assert!(target != Vec3::zeros());
+
+        camera.orbit(30.0, 10.0);
+        camera.orbit(-5.0, 20.0);
+
+        let distance = nalgebra_glm::distance(&camera.position, &target);
+        assert!((distance - 1.5).abs() < 1e-4);
+        let aim = nalgebra_glm::normalize(&(target - camera.position));
+        assert!(nalgebra_glm::dot(&aim, &camera.front) > 1.0 - 1e-5);
+    }
+}
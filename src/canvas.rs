@@ -0,0 +1,38 @@
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+use crate::renderer::{context_2d, put_pixels, RenderError};
+
+pub fn push_rgba(rgba: &mut Vec<u8>, (r, g, b): (f32, f32, f32)) {
+    rgba.extend_from_slice(&[
+        (r.clamp(0.0, 1.0) * 255.0) as u8,
+        (g.clamp(0.0, 1.0) * 255.0) as u8,
+        (b.clamp(0.0, 1.0) * 255.0) as u8,
+        255,
+    ]);
+}
+
+// Resizes the canvas to the pixels and draws them, a canvas that isn't mounted is left alone
+pub fn put_rgba(canvas: &NodeRef, width: u32, height: u32, rgba: &[u8]) -> Result<(), RenderError> {
+    let canvas = match canvas.cast::<HtmlCanvasElement>() {
+        Some(canvas) => canvas,
+        None => return Ok(()),
+    };
+    canvas.set_width(width);
+    canvas.set_height(height);
+
+    put_pixels(&context_2d(&canvas)?, width, height, rgba)
+}
+
+// Why a panel's canvases are blank, in place of them
+pub fn view_draw_error(error: &Option<RenderError>) -> Html {
+    match error {
+        Some(error) => html! {
+            <div class="alert alert-danger">
+                <strong>{"The preview can't be drawn: "}</strong>
+                {error.to_string()}
+            </div>
+        },
+        None => html! {},
+    }
+}
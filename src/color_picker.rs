@@ -2,12 +2,16 @@ use wasm_bindgen::JsCast;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
+use crate::color::{cmyk_to_rgb, rgb_to_cmyk, rgb_to_str};
+use crate::dot_gain::{cmyk_to_rgb_with_gain, DotGainCurves};
+
 pub enum RGB {
     Red,
     Green,
     Blue,
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum CMYK {
     Cyan,
     Magenta,
@@ -21,21 +25,39 @@ pub enum Msg {
     ColorCmykChanged((f32, CMYK)),
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub struct PickedColor {
+    pub rgb: (f32, f32, f32),
+    pub cmyk: (f32, f32, f32, f32),
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub value: PickedColor,
+    pub onchange: Callback<PickedColor>,
+    pub dot_gain: DotGainCurves,
+}
+
 pub struct ColorPicker {
     rgb_string: String,
     rgb_value: (f32, f32, f32),
     cmyk_value: (f32, f32, f32, f32),
+    // The CMYK value as printed with the dot gain curves
+    printed: (f32, f32, f32),
 }
 
 impl Component for ColorPicker {
     type Message = Msg;
-    type Properties = ();
+    type Properties = Props;
 
     fn create(ctx: &Context<Self>) -> Self {
+        let value = ctx.props().value;
+
         Self {
-            rgb_string: "#ffffff".to_string(),
-            rgb_value: (1.0, 1.0, 1.0),
-            cmyk_value: (0.0, 0.0, 0.0, 0.0),
+            rgb_string: rgb_to_str(value.rgb),
+            rgb_value: value.rgb,
+            cmyk_value: value.cmyk,
+            printed: cmyk_to_rgb_with_gain(value.cmyk, &ctx.props().dot_gain),
         }
     }
 
@@ -178,6 +200,13 @@ impl Component for ColorPicker {
                             <span>{format!("{:.2}%", self.cmyk_value.3 * 100.0)}</span>
                         </div>
                     </div>
+                    <div class="col-auto text-center">
+                        <div style={format!(
+                            "background-color: {}; width: 48px; height: 48px;",
+                            rgb_to_str(self.printed)
+                        )}></div>
+                        <small>{"Printed"}</small>
+                    </div>
                 </div>
             </div>
         }
@@ -199,8 +228,6 @@ impl Component for ColorPicker {
 
                 self.rgb_value = (r / 255.0, g / 255.0, b / 255.0);
                 self.cmyk_value = rgb_to_cmyk(self.rgb_value);
-
-                true
             }
             Msg::ColorRgbChanged((value, id)) => {
                 let val_str = format!("{:02x}", (value * 255.0) as u8);
@@ -220,8 +247,6 @@ impl Component for ColorPicker {
                 }
 
                 self.cmyk_value = rgb_to_cmyk(self.rgb_value);
-
-                true
             }
             Msg::ColorCmykChanged((value, id)) => {
                 match id {
@@ -241,41 +266,32 @@ impl Component for ColorPicker {
 
                 self.rgb_value = cmyk_to_rgb(self.cmyk_value);
                 self.rgb_string = rgb_to_str(self.rgb_value);
-
-                true
             }
         }
-    }
-}
 
-fn rgb_to_cmyk((r, g, b): (f32, f32, f32)) -> (f32, f32, f32, f32) {
-    let k = 1.0 - r.max(g).max(b);
-    if k >= 1.0 {
-        return (1.0, 1.0, 1.0, 1.0);
-    }
+        self.printed = cmyk_to_rgb_with_gain(self.cmyk_value, &ctx.props().dot_gain);
+        ctx.props().onchange.emit(PickedColor {
+            rgb: self.rgb_value,
+            cmyk: self.cmyk_value,
+        });
 
-    let c = (1.0 - r - k) / (1.0 - k);
-    let m = (1.0 - g - k) / (1.0 - k);
-    let y = (1.0 - b - k) / (1.0 - k);
-
-    (c, m, y, k)
-}
+        true
+    }
 
-fn rgb_to_str((r, g, b): (f32, f32, f32)) -> String {
-    format!(
-        "#{:02x}{:02x}{:02x}",
-        (r * 255.0) as u8,
-        (g * 255.0) as u8,
-        (b * 255.0) as u8
-    )
-}
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let value = ctx.props().value;
+        let printed = cmyk_to_rgb_with_gain(value.cmyk, &ctx.props().dot_gain);
+        if value.rgb == self.rgb_value && value.cmyk == self.cmyk_value && printed == self.printed {
+            return false;
+        }
 
-fn cmyk_to_rgb((c, m, y, k): (f32, f32, f32, f32)) -> (f32, f32, f32) {
-    let r = (1.0 - c) * (1.0 - k);
-    let g = (1.0 - m) * (1.0 - k);
-    let b = (1.0 - y) * (1.0 - k);
+        self.printed = printed;
+        self.rgb_value = value.rgb;
+        self.cmyk_value = value.cmyk;
+        self.rgb_string = rgb_to_str(self.rgb_value);
 
-    (r, g, b)
+        true
+    }
 }
 
 fn extract_input_element(event: InputEvent) -> HtmlInputElement {
@@ -1,13 +1,34 @@
-use js_sys::{Float32Array, Uint32Array};
-use nalgebra::{Unit, UnitQuaternion};
-use wasm_bindgen::JsCast;
-use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlShader, WebGlVertexArrayObject};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_events::{EventListener, EventListenerOptions};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, HtmlInputElement, HtmlSelectElement,
+    WebGl2RenderingContext,
+};
 use yew::prelude::*;
 
+use crate::animation::{now, AnimationLoop};
+use crate::arcball::Arcball;
+use crate::camera::Camera;
+use crate::export::{
+    download_png, downsample, export_size, widest_export, DEFAULT_EXPORT_WIDTH, MIN_EXPORT_WIDTH,
+};
+use crate::gamuts::{wireframe_vertices, GAMUTS};
+use crate::guides::{axis_vertices, edge_vertices, project_to_screen, tick_vertices};
+use crate::rasterizer::{DrawState, SoftwareTarget};
+use crate::renderer::{
+    context_2d, put_pixels, Mesh, RenderError, RenderTarget, ShaderProgram, VertexLayout,
+    VolumeTexture,
+};
+use crate::resize::{display_aspect, fit_canvas_to_display, ResizeWatcher};
+use crate::solids::{ColorVolume, Solid, SOLIDS, VOLUME_EXTENT, VOLUME_SIZE};
+use crate::voxels::{voxel_instances, VoxelOptions, MAX_RESOLUTION, MAX_SPACING, MIN_RESOLUTION};
+
 //https://www.cubic.org/docs/3dclip.htm#ma4
 //https://glbook.gamedev.net/GLBOOK/glbook.gamedev.net/moglgp/advclip.html
 
-use nalgebra_glm::{Mat4x4, Vec3};
+use nalgebra_glm::{Mat4x4, Vec2, Vec3, Vec4};
 
 const VERT_SHADER: &str = r#"#version 300 es
 
@@ -32,123 +53,1417 @@ const FRAG_SHADER: &str = r#"#version 300 es
 
     in vec3 v_color;
 
+    uniform float u_alpha;
+
     out vec4 color;
 
     void main() {
-        color = vec4(v_color, 1.0);
+        color = vec4(v_color, u_alpha);
     }
 "#;
 
-const CROSS_VERT_SHADER: &str = r#"#version 300 es
-    
-        in vec2 a_position;
-        in vec3 a_color;
+const POINT_VERT_SHADER: &str = r#"#version 300 es
+
+    in vec3 a_position;
+    in vec3 a_color;
 
-        uniform mat4 u_model;
-        uniform mat4 u_view;
-        uniform mat4 u_projection;
+    uniform mat4 u_model;
+    uniform mat4 u_view;
+    uniform mat4 u_projection;
+    uniform float u_point_size;
 
-        out vec3 v_color;
+    out vec3 v_color;
 
-        void main() {
-            vec4 color = u_view * u_model * vec4(a_color, 1.0);
-            v_color = color.xyz;
-            gl_Position = u_projection * vec4(a_position, -1.0, 1.0);
-        }
+    void main() {
+        v_color = a_color;
+        gl_Position = u_projection * u_view * u_model * vec4(a_position, 1.0);
+        gl_PointSize = u_point_size;
+    }
 "#;
 
-const CROSS_FRAG_SHADER: &str = r#"#version 300 es
+const POINT_FRAG_SHADER: &str = r#"#version 300 es
 
     precision mediump float;
 
     in vec3 v_color;
 
+    uniform bool u_round_points;
+
     out vec4 color;
 
     void main() {
+        if (u_round_points && length(gl_PointCoord - vec2(0.5)) > 0.5) {
+            discard;
+        }
+
         color = vec4(v_color, 1.0);
     }
 "#;
 
+// A unit cube per instance, moved to its voxel's center and shrunk to its size
+const VOXEL_VERT_SHADER: &str = r#"#version 300 es
+
+    in vec3 a_position;
+    in vec3 a_offset;
+    in vec3 a_color;
+
+    uniform mat4 u_model;
+    uniform mat4 u_view;
+    uniform mat4 u_projection;
+    uniform float u_voxel_size;
+    uniform float u_spacing;
+    uniform bool u_cutaway;
+    uniform float u_cut_depth;
+
+    out vec3 v_color;
+
+    void main() {
+        vec3 center = a_offset * (1.0 + u_spacing);
+        vec4 view_center = u_view * u_model * vec4(center, 1.0);
+
+        v_color = a_color;
+        if (u_cutaway && view_center.z > u_cut_depth) {
+            // Every corner outside the clip volume drops the whole voxel
+            gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        } else {
+            vec3 position = center + a_position * u_voxel_size;
+            gl_Position = u_projection * u_view * u_model * vec4(position, 1.0);
+        }
+    }
+"#;
+
+// Cube faces are see-through while a point cloud is shown so the points inside stay visible
+const CUBE_ALPHA_WITH_POINTS: f32 = 0.15;
+const POINT_SIZE: f32 = 2.0;
+const PALETTE_POINT_SIZE: f32 = 14.0;
+// Wide gamut wireframes stay faint enough to read the hull through them
+const GAMUT_ALPHA: f32 = 0.35;
+
+// Canvases fill their half of the row, the backing store follows their displayed size
+const CANVAS_STYLE: &str = "flex: 1 1 0; min-width: 0; height: 50vh;";
+const LABEL_STYLE: &str = "position: absolute; left: 0; top: 0; display: none; \
+                           transform: translate(6px, -50%); color: white; font-size: 12px; \
+                           text-shadow: 0 0 3px black; white-space: nowrap; pointer-events: none;";
+
+// A quad over the whole canvas, each corner placed on the slice plane in model space
+const CROSS_VERT_SHADER: &str = r#"#version 300 es
+
+    in vec2 a_position;
+
+    uniform mat4 u_to_model;
+    uniform vec3 u_center;
+    uniform vec2 u_extent;
+
+    out vec3 v_position;
+
+    void main() {
+        vec3 view_position = u_center + vec3(a_position * u_extent, 0.0);
+        v_position = (u_to_model * vec4(view_position, 1.0)).xyz;
+        gl_Position = vec4(a_position, 0.0, 1.0);
+    }
+"#;
+
+// Colors the slice from the solid's ColorVolume, same lookup as ColorVolume::sample
+const CROSS_FRAG_SHADER: &str = r#"#version 300 es
+
+    precision highp float;
+    precision highp sampler3D;
+
+    in vec3 v_position;
+
+    uniform sampler3D u_volume;
+    uniform float u_volume_extent;
+
+    out vec4 color;
+
+    void main() {
+        vec3 coords = v_position / (2.0 * u_volume_extent) + 0.5;
+        if (any(lessThan(coords, vec3(0.0))) || any(greaterThan(coords, vec3(1.0)))) {
+            discard;
+        }
+
+        // Outside texels are transparent black, so dividing by coverage undoes their darkening
+        vec4 texel = texture(u_volume, coords);
+        if (texel.a < 0.5) {
+            discard;
+        }
+        color = vec4(texel.rgb / texel.a, 1.0);
+    }
+"#;
+
+// Half the height of the cross-section canvas in model units, enough for the cube's diagonal
+const SLICE_EXTENT: f32 = 0.9;
+
+// Camera and model orientation, moved by the app and read by the cube's frame loop so moving them
+// doesn't take a render
+pub struct ViewState {
+    pub camera: Camera,
+    pub arcball: Arcball,
+}
+
+// Compared by identity, the cube reads the current state every frame
+#[derive(Clone)]
+pub struct SharedView(pub Rc<RefCell<ViewState>>);
+
+impl PartialEq for SharedView {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
 #[derive(Properties, Clone, PartialEq)]
 pub struct Props {
-    pub view: Mat4x4,
-    pub projection: Mat4x4,
-    pub onmousemove: Callback<MouseEvent>,
-    pub onmousedown: Callback<MouseEvent>,
-    pub onmouseup: Callback<MouseEvent>,
-    pub x_rotation: f32,
-    pub y_rotation: f32,
+    pub view: SharedView,
+    pub onpointerdown: Callback<PointerEvent>,
+    pub onpointermove: Callback<PointerEvent>,
+    // Also called when the browser cancels the pointer
+    pub onpointerup: Callback<PointerEvent>,
+    pub onwheel: Callback<WheelEvent>,
+    // Width over height of the cube canvas whenever it changes
+    pub onresize: Callback<f32>,
+    pub points: Rc<Vec<f32>>,
+    pub palette: Rc<Vec<f32>>,
+    pub gradient_path: Rc<Vec<f32>>,
+}
+
+// Settings of the cube view that aren't props
+#[derive(Clone, Copy)]
+struct ViewOptions {
+    solid: Solid,
+    // Which of GAMUTS are outlined around the hulls
+    gamuts: [bool; GAMUTS.len()],
+    ticks: bool,
+    show_voxels: bool,
+    voxels: VoxelOptions,
+    // Slice plane distance toward the camera from the cube's center
+    slice_offset: f32,
+}
+
+#[derive(Clone, Copy)]
+pub enum ExportCanvas {
+    Cube,
+    CrossSection,
 }
 
 pub enum Msg {
     PosChanged(Vec3),
+    SolidChanged(Solid),
+    GamutToggled(usize),
+    Resized,
+    Frame(f64),
+    ExportWidthChanged(u32),
+    ExportTransparentToggled,
+    Export(ExportCanvas),
+    TicksToggled,
+    VoxelsToggled,
+    VoxelResolutionChanged(u32),
+    VoxelSpacingChanged(f32),
+    CutawayToggled,
+    CreateBackend,
+    ContextLost,
+    ContextRestored,
 }
 
-pub struct Cube {
-    canvas: NodeRef,
-    gl: Option<WebGl2RenderingContext>,
-    shader_program: Option<WebGlProgram>,
-    va: Option<WebGlVertexArrayObject>,
+// Everything a frame is drawn from, to tell whether anything changed since the last one
+struct Scene {
     view: Mat4x4,
+    projection: Mat4x4,
+    model: Mat4x4,
+    points: Rc<Vec<f32>>,
+    palette: Rc<Vec<f32>>,
+    gradient_path: Rc<Vec<f32>>,
+}
+
+impl Scene {
+    fn new(props: &Props) -> Self {
+        let view = props.view.0.borrow();
+        Self {
+            view: view.camera.calculate_view_matrix(),
+            projection: view.camera.calculate_projection_matrix(),
+            model: view.arcball.model_matrix(),
+            points: props.points.clone(),
+            palette: props.palette.clone(),
+            gradient_path: props.gradient_path.clone(),
+        }
+    }
+}
+
+impl PartialEq for Scene {
+    fn eq(&self, other: &Self) -> bool {
+        // Vertex data is compared by identity, a new Rc means new points
+        self.view == other.view
+            && self.projection == other.projection
+            && self.model == other.model
+            && Rc::ptr_eq(&self.points, &other.points)
+            && Rc::ptr_eq(&self.palette, &other.palette)
+            && Rc::ptr_eq(&self.gradient_path, &other.gradient_path)
+    }
+}
+
+const STATS_INTERVAL_MS: f64 = 500.0;
+// Largest side the CPU renders exports at, which also caps their size. Exports more than half as
+// big skip supersampling.
+const SOFTWARE_EXPORT_MAX_SIZE: u32 = 4096;
+
+#[derive(Default)]
+struct FrameStats {
+    since: f64,
+    frames: u32,
+    draws: u32,
+    draw_ms: f64,
+    max_draw_ms: f64,
+    summary: String,
+}
+
+impl FrameStats {
+    // Counts a frame and the time spent drawing it if it was drawn, returns whether the summary
+    // changed
+    fn record(&mut self, now: f64, draw_ms: Option<f64>) -> bool {
+        if self.frames == 0 && self.since == 0.0 {
+            self.since = now;
+        }
+
+        self.frames += 1;
+        if let Some(draw_ms) = draw_ms {
+            self.draws += 1;
+            self.draw_ms += draw_ms;
+            self.max_draw_ms = self.max_draw_ms.max(draw_ms);
+        }
+
+        let elapsed = now - self.since;
+        if elapsed < STATS_INTERVAL_MS {
+            return false;
+        }
+
+        let summary = format!(
+            "{:.0} fps, {:.0} redraws/s, draw {:.2} ms avg / {:.2} ms max",
+            self.frames as f64 * 1000.0 / elapsed,
+            self.draws as f64 * 1000.0 / elapsed,
+            self.draw_ms / self.draws.max(1) as f64,
+            self.max_draw_ms,
+        );
+        *self = Self {
+            since: now,
+            summary: self.summary.clone(),
+            ..Self::default()
+        };
+
+        if summary == self.summary {
+            return false;
+        }
+        self.summary = summary;
+        true
+    }
+}
+
+// Cube corners with their RGB colors, x is blue, y is red and z is green
+pub const CUBE_VERTICES: [f32; 48] = [
+    // front
+    -0.5, -0.5, 0.5, 0.0, 1.0, 0.0, // bottom left
+    0.5, -0.5, 0.5, 0.0, 1.0, 1.0, // bottom right
+    0.5, 0.5, 0.5, 1.0, 1.0, 1.0, // top right
+    -0.5, 0.5, 0.5, 1.0, 1.0, 0.0, // top left
+    // back
+    -0.5, -0.5, -0.5, 0.0, 0.0, 0.0, // bottom left
+    0.5, -0.5, -0.5, 0.0, 0.0, 1.0, // bottom right
+    0.5, 0.5, -0.5, 1.0, 0.0, 1.0, // top right
+    -0.5, 0.5, -0.5, 1.0, 0.0, 0.0, // top left
+];
+
+pub const CUBE_INDICES: [u32; 36] = [
+    0, 1, 2, 2, 3, 0, // front
+    4, 5, 6, 6, 7, 4, // back
+    0, 4, 7, 7, 3, 0, // left
+    1, 5, 6, 6, 2, 1, // right
+    3, 2, 6, 6, 7, 3, // top
+    0, 1, 5, 5, 4, 0, // bottom
+];
+
+// Corners of the cross-section canvas in clip space
+const SLICE_VERTICES: [f32; 8] = [
+    -1.0, -1.0, // bottom left
+    1.0, -1.0, // bottom right
+    1.0, 1.0, // top right
+    -1.0, 1.0, // top left
+];
+
+const SLICE_INDICES: [u32; 6] = [0, 1, 2, 2, 3, 0];
+
+// The slice plane faces the camera through the cube's center, moved toward it by slice_offset.
+// Returns the matrix from view space back to model space and the plane's center in view space.
+fn slice_frame(view: &Mat4x4, model: &Mat4x4, slice_offset: f32) -> (Mat4x4, Vec3) {
+    let model_view = view * model;
+    let center = (model_view * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+
+    (
+        model_view.try_inverse().unwrap_or_else(Mat4x4::identity),
+        center + Vec3::new(0.0, 0.0, slice_offset),
+    )
+}
+
+// GL state of both canvases, created on first render
+struct Scenes {
+    target: RenderTarget,
+    cube_program: ShaderProgram,
+    points_program: ShaderProgram,
+    solid: Mesh,
+    image_points: Mesh,
+    palette_points: Mesh,
+    gradient_path: Mesh,
+    // Edges and axis arrows
+    guides: Mesh,
+    ticks: Mesh,
+    voxel_program: ShaderProgram,
+    voxels: Mesh,
+    // One per entry of GAMUTS, empty unless the solid is a hull
+    gamut_wireframes: Vec<Mesh>,
+    // Resolution and solid the voxel instances were built for
+    voxel_instances: Option<(u32, Solid)>,
+    // Solid the meshes and points were last uploaded for
+    uploaded_solid: Option<Solid>,
+
+    crosssection_target: RenderTarget,
+    crosssection_program: ShaderProgram,
+    crosssection: Mesh,
+    // The solid's ColorVolume, uploaded with its meshes
+    crosssection_volume: VolumeTexture,
+}
+
+impl Scenes {
+    fn new(
+        canvas: HtmlCanvasElement,
+        crosssection: HtmlCanvasElement,
+    ) -> Result<Self, RenderError> {
+        let target = RenderTarget::new(canvas)?;
+        let gl = target.gl();
+
+        gl.enable(WebGl2RenderingContext::DEPTH_TEST);
+        // Alpha accumulates like coverage so exports with a transparent background come out right
+        gl.blend_func_separate(
+            WebGl2RenderingContext::SRC_ALPHA,
+            WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA,
+            WebGl2RenderingContext::ONE,
+            WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA,
+        );
+
+        let cube_program = ShaderProgram::new(gl, VERT_SHADER, FRAG_SHADER)?;
+        let solid = Mesh::new(
+            gl,
+            &cube_program,
+            VertexLayout::position_color(),
+            WebGl2RenderingContext::TRIANGLES,
+        );
+
+        let points_program = ShaderProgram::new(gl, POINT_VERT_SHADER, POINT_FRAG_SHADER)?;
+        let points_mesh =
+            |mode| Mesh::new(gl, &points_program, VertexLayout::position_color(), mode);
+        let image_points = points_mesh(WebGl2RenderingContext::POINTS);
+        let palette_points = points_mesh(WebGl2RenderingContext::POINTS);
+        let gradient_path = points_mesh(WebGl2RenderingContext::LINE_STRIP);
+
+        let guides = points_mesh(WebGl2RenderingContext::LINES);
+        let ticks = points_mesh(WebGl2RenderingContext::LINES);
+
+        let voxel_program = ShaderProgram::new(gl, VOXEL_VERT_SHADER, FRAG_SHADER)?;
+        let mut voxels = Mesh::new_instanced(
+            gl,
+            &voxel_program,
+            // The corner colors are ignored, each voxel has one color
+            VertexLayout::new(&[("a_position", 3), ("a_corner_color", 3)]),
+            VertexLayout::new(&[("a_offset", 3), ("a_color", 3)]),
+            WebGl2RenderingContext::TRIANGLES,
+        );
+        voxels.set_vertices(gl, &CUBE_VERTICES);
+        voxels.set_indices(gl, &CUBE_INDICES);
+
+        let gamut_wireframes = GAMUTS
+            .iter()
+            .map(|_| {
+                Mesh::new(
+                    gl,
+                    &cube_program,
+                    VertexLayout::position_color(),
+                    WebGl2RenderingContext::LINES,
+                )
+            })
+            .collect();
+
+        let crosssection_target = RenderTarget::new(crosssection)?;
+        let gl = crosssection_target.gl();
+
+        let crosssection_program = ShaderProgram::new(gl, CROSS_VERT_SHADER, CROSS_FRAG_SHADER)?;
+        let mut crosssection = Mesh::new(
+            gl,
+            &crosssection_program,
+            VertexLayout::new(&[("a_position", 2)]),
+            WebGl2RenderingContext::TRIANGLES,
+        );
+        crosssection.set_vertices(gl, &SLICE_VERTICES);
+        crosssection.set_indices(gl, &SLICE_INDICES);
+        let crosssection_volume = VolumeTexture::new(gl);
+
+        Ok(Self {
+            target,
+            cube_program,
+            points_program,
+            solid,
+            image_points,
+            palette_points,
+            gradient_path,
+            guides,
+            ticks,
+            voxel_program,
+            voxels,
+            gamut_wireframes,
+            voxel_instances: None,
+            uploaded_solid: None,
+            crosssection_target,
+            crosssection_program,
+            crosssection,
+            crosssection_volume,
+        })
+    }
+
+    // The points program also draws lines in their vertex colors, unlit
+    fn use_points_program(&self, scene: &Scene, round_points: bool) -> &ShaderProgram {
+        let gl = self.target.gl();
+        let program = &self.points_program;
+
+        program.use_program(gl);
+        program.set_uniform(gl, "u_model", &scene.model);
+        program.set_uniform(gl, "u_view", &scene.view);
+        program.set_uniform(gl, "u_projection", &scene.projection);
+        program.set_uniform(gl, "u_round_points", &round_points);
+        program
+    }
+
+    fn draw_points(&self, scene: &Scene, mesh: &Mesh, point_size: f32) {
+        let gl = self.target.gl();
+        let program = self.use_points_program(scene, true);
+
+        program.set_uniform(gl, "u_point_size", &point_size);
+        mesh.draw(gl);
+    }
+
+    fn draw_lines(&self, scene: &Scene, mesh: &Mesh) {
+        self.use_points_program(scene, false);
+        mesh.draw(self.target.gl());
+    }
+
+    fn upload(&mut self, scene: &Scene, options: &ViewOptions) {
+        let gl = self.target.gl();
+        let solid = options.solid;
+
+        if self.uploaded_solid != Some(solid) {
+            let (vertices, indices) = solid.mesh();
+            self.solid.set_vertices(gl, &vertices);
+            self.solid.set_indices(gl, &indices);
+            self.guides
+                .set_vertices(gl, &[edge_vertices(solid), axis_vertices(solid)].concat());
+            self.ticks.set_vertices(gl, &tick_vertices(solid));
+            let vertices = gamut_wireframe_vertices(solid);
+            for (vertices, wireframe) in vertices.iter().zip(&mut self.gamut_wireframes) {
+                wireframe.set_vertices(gl, vertices);
+            }
+            self.crosssection_volume.set_texels(
+                self.crosssection_target.gl(),
+                VOLUME_SIZE,
+                ColorVolume::new(solid).texels(),
+            );
+
+            // The points move to where their colors sit in the new solid
+            self.image_points.forget_shared();
+            self.palette_points.forget_shared();
+            self.gradient_path.forget_shared();
+            self.uploaded_solid = Some(solid);
+        }
+
+        let place = |vertices: &[f32]| solid.place_points(vertices);
+        self.image_points
+            .set_shared_vertices(gl, &scene.points, place);
+        self.palette_points
+            .set_shared_vertices(gl, &scene.palette, place);
+        self.gradient_path
+            .set_shared_vertices(gl, &scene.gradient_path, place);
+
+        let instances = (options.voxels.resolution, solid);
+        if options.show_voxels && solid.is_cube() && self.voxel_instances != Some(instances) {
+            self.voxels
+                .set_instances(gl, &voxel_instances(instances.0, solid));
+            self.voxel_instances = Some(instances);
+        }
+    }
+
+    // Point sizes are in pixels, point_scale keeps them in proportion on bigger exports
+    fn draw_cube(
+        &self,
+        scene: &Scene,
+        background: (f32, f32, f32, f32),
+        point_scale: f32,
+        options: &ViewOptions,
+    ) {
+        let gl = self.target.gl();
+        self.target.begin_frame(background);
+
+        // draw image point cloud, palette centers and gradient path
+        self.draw_points(scene, &self.image_points, POINT_SIZE * point_scale);
+        self.draw_points(
+            scene,
+            &self.palette_points,
+            PALETTE_POINT_SIZE * point_scale,
+        );
+        self.draw_lines(scene, &self.gradient_path);
+
+        self.draw_lines(scene, &self.guides);
+        if options.ticks {
+            self.draw_lines(scene, &self.ticks);
+        }
+
+        let show_voxels = options.show_voxels && options.solid.is_cube();
+        let (program, mesh) = if show_voxels {
+            (&self.voxel_program, &self.voxels)
+        } else {
+            (&self.cube_program, &self.solid)
+        };
+        program.use_program(gl);
+        program.set_uniform(gl, "u_model", &scene.model);
+        program.set_uniform(gl, "u_view", &scene.view);
+        program.set_uniform(gl, "u_projection", &scene.projection);
+
+        if show_voxels {
+            let voxels = &options.voxels;
+            let center_depth = (scene.view * scene.model * Vec4::new(0.0, 0.0, 0.0, 1.0)).z;
+
+            program.set_uniform(gl, "u_voxel_size", &(1.0 / voxels.resolution as f32));
+            program.set_uniform(gl, "u_spacing", &voxels.spacing);
+            program.set_uniform(gl, "u_cutaway", &voxels.cutaway);
+            program.set_uniform(gl, "u_cut_depth", &(center_depth + options.slice_offset));
+        }
+
+        let has_points = !(self.image_points.is_empty()
+            && self.palette_points.is_empty()
+            && self.gradient_path.is_empty());
+        if has_points {
+            program.set_uniform(gl, "u_alpha", &CUBE_ALPHA_WITH_POINTS);
+            gl.enable(WebGl2RenderingContext::BLEND);
+            gl.depth_mask(false);
+        } else {
+            program.set_uniform(gl, "u_alpha", &1.0);
+        }
+
+        mesh.draw(gl);
+
+        // The hull hides the parts of the wider gamuts behind it
+        let program = &self.cube_program;
+        program.use_program(gl);
+        program.set_uniform(gl, "u_alpha", &GAMUT_ALPHA);
+        gl.enable(WebGl2RenderingContext::BLEND);
+        gl.depth_mask(false);
+        for (shown, wireframe) in options.gamuts.iter().zip(&self.gamut_wireframes) {
+            if *shown {
+                wireframe.draw(gl);
+            }
+        }
+
+        gl.disable(WebGl2RenderingContext::BLEND);
+        gl.depth_mask(true);
+    }
+
+    // draw the solid's slice on the cutaway plane
+    fn draw_crosssection(
+        &self,
+        scene: &Scene,
+        background: (f32, f32, f32, f32),
+        options: &ViewOptions,
+    ) {
+        let gl = self.crosssection_target.gl();
+        let program = &self.crosssection_program;
+        self.crosssection_target.begin_frame(background);
+
+        let aspect = self.crosssection_target.aspect();
+        let (to_model, center) = slice_frame(&scene.view, &scene.model, options.slice_offset);
+        let extent = Vec2::new(SLICE_EXTENT * aspect, SLICE_EXTENT);
+
+        program.use_program(gl);
+        program.set_uniform(gl, "u_to_model", &to_model);
+        program.set_uniform(gl, "u_center", &center);
+        program.set_uniform(gl, "u_extent", &extent);
+        self.crosssection_volume.bind(gl, 0);
+        program.set_uniform(gl, "u_volume", &0);
+        program.set_uniform(gl, "u_volume_extent", &VOLUME_EXTENT);
+        self.crosssection.draw(gl);
+    }
+
+    fn draw(&mut self, scene: &Scene, options: &ViewOptions) {
+        self.upload(scene, options);
+        self.draw_cube(scene, (0.0, 0.0, 0.0, 1.0), 1.0, options);
+        self.draw_crosssection(scene, (0.0, 0.0, 0.0, 1.0), options);
+    }
+
+    // Renders one canvas offscreen at the given width, returns its pixels, width and height
+    fn export(
+        &mut self,
+        scene: &Scene,
+        canvas: ExportCanvas,
+        width: u32,
+        transparent: bool,
+        options: &ViewOptions,
+    ) -> Result<(Vec<u8>, u32, u32), RenderError> {
+        self.upload(scene, options);
+
+        let target = match canvas {
+            ExportCanvas::Cube => &self.target,
+            ExportCanvas::CrossSection => &self.crosssection_target,
+        };
+        let (width, height, factor) = export_size(width, target.aspect(), target.max_size());
+        let (render_width, render_height) = (width * factor, height * factor);
+        let background = (0.0, 0.0, 0.0, if transparent { 0.0 } else { 1.0 });
+
+        let pixels = match canvas {
+            ExportCanvas::Cube => {
+                let point_scale = render_height as f32 / target.size().1.max(1) as f32;
+                target.capture(render_width, render_height, || {
+                    self.draw_cube(scene, background, point_scale, options)
+                })?
+            }
+            ExportCanvas::CrossSection => target.capture(render_width, render_height, || {
+                self.draw_crosssection(scene, background, options)
+            })?,
+        };
+
+        Ok((
+            downsample(&pixels, render_width, render_height, factor),
+            width,
+            height,
+        ))
+    }
+}
+
+// The solid pass on the CPU, same mesh and shader math as the WebGL version
+fn draw_cube_software(
+    target: &mut SoftwareTarget,
+    solid: Solid,
+    model: &Mat4x4,
+    view: &Mat4x4,
+    projection: &Mat4x4,
+    alpha: f32,
+) {
+    let mvp = projection * view * model;
+    let state = DrawState {
+        depth_write: alpha >= 1.0,
+        blend: alpha < 1.0,
+        ..DrawState::default()
+    };
+
+    let (vertices, indices) = solid.mesh();
+    target.draw_triangles(
+        &vertices,
+        &VertexLayout::position_color(),
+        &indices,
+        state,
+        |vertex| {
+            (
+                mvp * Vec4::new(vertex[0], vertex[1], vertex[2], 1.0),
+                Vec3::new(vertex[3], vertex[4], vertex[5]),
+            )
+        },
+        |color| [color.x, color.y, color.z, alpha],
+    );
+}
+
+// The points pass on the CPU
+fn draw_points_software(
+    target: &mut SoftwareTarget,
+    mvp: &Mat4x4,
+    vertices: &[f32],
+    point_size: f32,
+) {
+    target.draw_points(
+        vertices,
+        &VertexLayout::position_color(),
+        point_size,
+        true,
+        |vertex| {
+            (
+                mvp * Vec4::new(vertex[0], vertex[1], vertex[2], 1.0),
+                Vec3::new(vertex[3], vertex[4], vertex[5]),
+            )
+        },
+        |color| [color.x, color.y, color.z, 1.0],
+    );
+}
+
+// Lines on the CPU, below full alpha they blend in without hiding what's behind like the gamut
+// wireframes
+fn draw_lines_software(
+    target: &mut SoftwareTarget,
+    mvp: &Mat4x4,
+    vertices: &[f32],
+    strip: bool,
+    alpha: f32,
+) {
+    let state = DrawState {
+        depth_write: alpha >= 1.0,
+        blend: alpha < 1.0,
+        ..DrawState::default()
+    };
+
+    target.draw_lines(
+        vertices,
+        &VertexLayout::position_color(),
+        strip,
+        state,
+        |vertex| {
+            (
+                mvp * Vec4::new(vertex[0], vertex[1], vertex[2], 1.0),
+                Vec3::new(vertex[3], vertex[4], vertex[5]),
+            )
+        },
+        |color| [color.x, color.y, color.z, alpha],
+    );
+}
+
+// The cross-section pass on the CPU, sampling the same volume the shader does
+fn draw_crosssection_software(
+    target: &mut SoftwareTarget,
+    volume: &ColorVolume,
+    view: &Mat4x4,
+    model: &Mat4x4,
+    slice_offset: f32,
+) {
+    let extent = Vec2::new(SLICE_EXTENT * target.aspect(), SLICE_EXTENT);
+    let (to_model, center) = slice_frame(view, model, slice_offset);
+
+    target.draw_triangles(
+        &SLICE_VERTICES,
+        &VertexLayout::new(&[("a_position", 2)]),
+        &SLICE_INDICES,
+        // Transparent fragments outside the solid blend away like the shader's discard
+        DrawState {
+            depth_test: false,
+            depth_write: false,
+            blend: true,
+        },
+        |vertex| {
+            let view_position = center + Vec3::new(vertex[0] * extent.x, vertex[1] * extent.y, 0.0);
+            (
+                Vec4::new(vertex[0], vertex[1], 0.0, 1.0),
+                (to_model * view_position.push(1.0)).xyz(),
+            )
+        },
+        |position| match volume.sample(position) {
+            Some((r, g, b)) => [r, g, b, 1.0],
+            None => [0.0, 0.0, 0.0, 0.0],
+        },
+    );
+}
+
+// Draws both canvases on the CPU through 2D contexts, for browsers without WebGL2
+// Outlines of the wider gamuts in the order of GAMUTS, only drawn around the hull
+fn gamut_wireframe_vertices(solid: Solid) -> [Vec<f32>; GAMUTS.len()] {
+    GAMUTS.map(|gamut| {
+        if solid.is_hull() {
+            wireframe_vertices(gamut, solid)
+        } else {
+            Vec::new()
+        }
+    })
+}
+
+struct SoftwareScenes {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    crosssection: HtmlCanvasElement,
+    crosssection_context: CanvasRenderingContext2d,
+    // Rebuilt when the solid changes
+    volume: ColorVolume,
+    gamut_wireframes: [Vec<f32>; GAMUTS.len()],
+}
+
+fn put_target(
+    context: &CanvasRenderingContext2d,
+    target: &SoftwareTarget,
+) -> Result<(), RenderError> {
+    put_pixels(context, target.width(), target.height(), &target.pixels())
+}
+
+impl SoftwareScenes {
+    fn new(
+        canvas: HtmlCanvasElement,
+        crosssection: HtmlCanvasElement,
+    ) -> Result<Self, RenderError> {
+        Ok(Self {
+            context: context_2d(&canvas)?,
+            crosssection_context: context_2d(&crosssection)?,
+            canvas,
+            crosssection,
+            volume: ColorVolume::new(Solid::RgbCube),
+            gamut_wireframes: gamut_wireframe_vertices(Solid::RgbCube),
+        })
+    }
+
+    fn update_solid(&mut self, solid: Solid) {
+        if self.volume.solid() != solid {
+            self.volume = ColorVolume::new(solid);
+            self.gamut_wireframes = gamut_wireframe_vertices(solid);
+        }
+    }
+
+    // Everything the WebGL pass draws but the voxels, in the same order
+    fn render_cube(
+        &self,
+        scene: &Scene,
+        (width, height): (u32, u32),
+        background: (f32, f32, f32, f32),
+        point_scale: f32,
+        options: &ViewOptions,
+    ) -> SoftwareTarget {
+        let solid = options.solid;
+        let mut target = SoftwareTarget::new(width, height);
+        let mvp = scene.projection * scene.view * scene.model;
+        target.begin_frame(background);
+
+        draw_points_software(
+            &mut target,
+            &mvp,
+            &solid.place_points(&scene.points),
+            POINT_SIZE * point_scale,
+        );
+        draw_points_software(
+            &mut target,
+            &mvp,
+            &solid.place_points(&scene.palette),
+            PALETTE_POINT_SIZE * point_scale,
+        );
+        let gradient_path = solid.place_points(&scene.gradient_path);
+        draw_lines_software(&mut target, &mvp, &gradient_path, true, 1.0);
+
+        let guides = [edge_vertices(solid), axis_vertices(solid)].concat();
+        draw_lines_software(&mut target, &mvp, &guides, false, 1.0);
+        if options.ticks {
+            draw_lines_software(&mut target, &mvp, &tick_vertices(solid), false, 1.0);
+        }
+
+        let has_points = !(scene.points.is_empty()
+            && scene.palette.is_empty()
+            && scene.gradient_path.is_empty());
+        let alpha = if has_points {
+            CUBE_ALPHA_WITH_POINTS
+        } else {
+            1.0
+        };
+        draw_cube_software(
+            &mut target,
+            solid,
+            &scene.model,
+            &scene.view,
+            &scene.projection,
+            alpha,
+        );
+
+        for (shown, wireframe) in options.gamuts.iter().zip(&self.gamut_wireframes) {
+            if *shown {
+                draw_lines_software(&mut target, &mvp, wireframe, false, GAMUT_ALPHA);
+            }
+        }
+
+        target
+    }
+
+    fn render_crosssection(
+        &self,
+        scene: &Scene,
+        (width, height): (u32, u32),
+        background: (f32, f32, f32, f32),
+        options: &ViewOptions,
+    ) -> SoftwareTarget {
+        let mut target = SoftwareTarget::new(width, height);
+        target.begin_frame(background);
+        draw_crosssection_software(
+            &mut target,
+            &self.volume,
+            &scene.view,
+            &scene.model,
+            options.slice_offset,
+        );
+
+        target
+    }
+
+    fn draw(&mut self, scene: &Scene, options: &ViewOptions) -> Result<(), RenderError> {
+        self.update_solid(options.solid);
+        let size = |canvas: &HtmlCanvasElement| (canvas.width(), canvas.height());
+        let background = (0.0, 0.0, 0.0, 1.0);
 
+        let cube = self.render_cube(scene, size(&self.canvas), background, 1.0, options);
+        put_target(&self.context, &cube)?;
+
+        let crosssection =
+            self.render_crosssection(scene, size(&self.crosssection), background, options);
+        put_target(&self.crosssection_context, &crosssection)
+    }
+
+    fn export(
+        &mut self,
+        scene: &Scene,
+        canvas: ExportCanvas,
+        width: u32,
+        transparent: bool,
+        options: &ViewOptions,
+    ) -> (Vec<u8>, u32, u32) {
+        self.update_solid(options.solid);
+        let displayed = match canvas {
+            ExportCanvas::Cube => &self.canvas,
+            ExportCanvas::CrossSection => &self.crosssection,
+        };
+        let (width, height, factor) =
+            export_size(width, display_aspect(displayed), SOFTWARE_EXPORT_MAX_SIZE);
+        let size = (width * factor, height * factor);
+        let background = (0.0, 0.0, 0.0, if transparent { 0.0 } else { 1.0 });
+
+        let target = match canvas {
+            ExportCanvas::Cube => {
+                let point_scale = size.1 as f32 / displayed.height().max(1) as f32;
+                self.render_cube(scene, size, background, point_scale, options)
+            }
+            ExportCanvas::CrossSection => {
+                self.render_crosssection(scene, size, background, options)
+            }
+        };
+
+        (
+            downsample(&target.pixels(), size.0, size.1, factor),
+            width,
+            height,
+        )
+    }
+}
+
+// Whichever way the canvases are being drawn
+enum Backend {
+    WebGl(Box<Scenes>),
+    Software(SoftwareScenes),
+}
+
+impl Backend {
+    // Falls back to drawing on the CPU only when there's no WebGL2 at all, a canvas that already
+    // has a WebGL context can't hand out a 2D one
+    fn new(
+        canvas: HtmlCanvasElement,
+        crosssection: HtmlCanvasElement,
+    ) -> Result<Self, RenderError> {
+        match Scenes::new(canvas.clone(), crosssection.clone()) {
+            Ok(scenes) => Ok(Self::WebGl(Box::new(scenes))),
+            Err(RenderError::Unsupported) => {
+                SoftwareScenes::new(canvas, crosssection).map(Self::Software)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // The CPU path draws everything but the voxels, their controls are hidden with it
+    fn draw(&mut self, scene: &Scene, options: &ViewOptions) -> Result<(), RenderError> {
+        match self {
+            Self::WebGl(scenes) => {
+                scenes.draw(scene, options);
+                Ok(())
+            }
+            Self::Software(scenes) => scenes.draw(scene, options),
+        }
+    }
+
+    // Widest image both canvases can be exported at with their height also within the limit
+    fn max_export_width(&self) -> u32 {
+        match self {
+            Self::WebGl(scenes) => [&scenes.target, &scenes.crosssection_target]
+                .iter()
+                .map(|target| widest_export(target.aspect(), target.max_size()))
+                .min()
+                .unwrap_or(SOFTWARE_EXPORT_MAX_SIZE),
+            Self::Software(scenes) => [&scenes.canvas, &scenes.crosssection]
+                .iter()
+                .map(|canvas| widest_export(display_aspect(canvas), SOFTWARE_EXPORT_MAX_SIZE))
+                .min()
+                .unwrap_or(SOFTWARE_EXPORT_MAX_SIZE),
+        }
+    }
+
+    fn export(
+        &mut self,
+        scene: &Scene,
+        canvas: ExportCanvas,
+        width: u32,
+        transparent: bool,
+        options: &ViewOptions,
+    ) -> Result<(Vec<u8>, u32, u32), RenderError> {
+        match self {
+            Self::WebGl(scenes) => scenes.export(scene, canvas, width, transparent, options),
+            Self::Software(scenes) => Ok(scenes.export(scene, canvas, width, transparent, options)),
+        }
+    }
+}
+
+pub struct Cube {
+    canvas: NodeRef,
     crosssection: NodeRef,
-    crosssection_ctx: Option<WebGl2RenderingContext>,
-    crosssection_shader_program: Option<WebGlProgram>,
-    crosssection_va: Option<WebGlVertexArrayObject>,
+    backend: Option<Backend>,
     crossection_pos: Vec3,
+
+    resize_watcher: Option<ResizeWatcher>,
+
+    // Why there's nothing to draw with, shown instead of a blank canvas
+    error: Option<RenderError>,
+    // Contexts lost and not yet restored, resources are rebuilt once all are back
+    lost_contexts: u32,
+    context_listeners: Vec<EventListener>,
+
+    // Frames are drawn from a requestAnimationFrame loop, only when the scene changed
+    animation: Option<AnimationLoop>,
+    drawn_scene: Option<Scene>,
+    dirty: bool,
+    stats: FrameStats,
+
+    solid: Solid,
+    show_gamuts: [bool; GAMUTS.len()],
+    // Names laid over the solid canvas, in the order of the solid's landmarks
+    labels: [NodeRef; 8],
+    show_ticks: bool,
+    show_voxels: bool,
+    voxels: VoxelOptions,
+
+    export_width: u32,
+    export_transparent: bool,
+    // Why the last export failed, until one succeeds
+    export_error: Option<String>,
 }
 
 impl Cube {
     pub fn new() -> Self {
         Self {
             canvas: NodeRef::default(),
-            gl: None,
-            shader_program: None,
-            va: None,
-            view: Mat4x4::identity(),
-            
             crosssection: NodeRef::default(),
-            crosssection_ctx: None,
-            crosssection_shader_program: None,
-            crosssection_va: None,
+            backend: None,
             crossection_pos: Vec3::new(0.0, 0.0, 0.0),
+
+            resize_watcher: None,
+
+            error: None,
+            lost_contexts: 0,
+            context_listeners: Vec::new(),
+
+            animation: None,
+            drawn_scene: None,
+            dirty: true,
+            stats: FrameStats::default(),
+
+            solid: Solid::RgbCube,
+            show_gamuts: [false; GAMUTS.len()],
+            labels: Default::default(),
+            show_ticks: false,
+            show_voxels: false,
+            voxels: VoxelOptions::default(),
+
+            export_width: DEFAULT_EXPORT_WIDTH,
+            export_transparent: false,
+            export_error: None,
         }
     }
+}
 
-    pub fn rotate(&mut self, angle: f32, axis: Vec3) {
-        let rotation = Mat4x4::from_axis_angle(&Unit::new_normalize(axis), angle);
-        self.view = rotation * self.view;
+impl Cube {
+    fn create_backend(&mut self) {
+        let canvas = self.canvas.cast::<HtmlCanvasElement>().unwrap();
+        let crosssection = self.crosssection.cast::<HtmlCanvasElement>().unwrap();
+
+        match Backend::new(canvas, crosssection) {
+            Ok(backend) => {
+                self.backend = Some(backend);
+                self.error = None;
+            }
+            Err(error) => {
+                log::error!("{}", error);
+                self.backend = None;
+                self.error = Some(error);
+            }
+        }
+
+        self.drawn_scene = None;
+        self.dirty = true;
+    }
+
+    fn view_status(&self) -> Html {
+        let status = if let Some(error) = &self.error {
+            html! {
+                <div class="alert alert-danger">
+                    <strong>{"The cube can't be drawn: "}</strong>
+                    {error.to_string()}
+                    if let Some(log) = error.log() {
+                        <pre class="mb-0">{log}</pre>
+                    }
+                </div>
+            }
+        } else if self.lost_contexts > 0 {
+            html! {
+                <div class="alert alert-warning">
+                    {"The graphics context was lost, waiting for the browser to restore it."}
+                </div>
+            }
+        } else if let Some(Backend::Software(_)) = &self.backend {
+            html! {
+                <small class="text-muted">{"WebGL2 isn't available, drawing on the CPU. "}</small>
+            }
+        } else {
+            html! {}
+        };
+
+        html! {
+            <>
+                {status}
+                if let Some(error) = &self.export_error {
+                    <div class="alert alert-danger">{error}</div>
+                }
+            </>
+        }
+    }
+
+    fn max_export_width(&self) -> u32 {
+        self.backend
+            .as_ref()
+            .map_or(SOFTWARE_EXPORT_MAX_SIZE, Backend::max_export_width)
+    }
+
+    fn view_options(&self) -> ViewOptions {
+        ViewOptions {
+            solid: self.solid,
+            gamuts: self.show_gamuts,
+            ticks: self.show_ticks,
+            show_voxels: self.show_voxels,
+            voxels: self.voxels,
+            slice_offset: self.crossection_pos.z,
+        }
+    }
+
+    // Keeps the labels over their landmarks, projected like the solid itself
+    fn position_labels(&self, scene: &Scene) {
+        let canvas = match self.canvas.cast::<HtmlCanvasElement>() {
+            Some(canvas) => canvas,
+            None => return,
+        };
+        let (width, height) = (canvas.client_width() as f32, canvas.client_height() as f32);
+        let mvp = scene.projection * scene.view * scene.model;
+
+        for ((_, position), label) in self.solid.landmarks().iter().zip(&self.labels) {
+            let style = match label.cast::<HtmlElement>() {
+                Some(label) => label.style(),
+                None => continue,
+            };
+
+            match project_to_screen(&mvp, *position, width, height) {
+                Some((x, y)) => {
+                    style.set_property("display", "block").ok();
+                    style.set_property("left", &format!("{}px", x)).ok();
+                    style.set_property("top", &format!("{}px", y)).ok();
+                }
+                None => {
+                    style.set_property("display", "none").ok();
+                }
+            }
+        }
+    }
+
+    fn view_solid(&self, ctx: &Context<Self>) -> Html {
+        let on_solid_change = ctx.link().batch_callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>();
+
+            select
+                .value()
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| SOLIDS.get(index))
+                .map(|solid| Msg::SolidChanged(*solid))
+        });
+
+        let solids = SOLIDS.iter().enumerate().map(|(index, solid)| {
+            html! {
+                <option value={index.to_string()} selected={*solid == self.solid}>
+                    {solid.name()}
+                </option>
+            }
+        });
+
+        // Wider gamuts only have a place around the perceptual hulls
+        let gamuts = GAMUTS.iter().enumerate().map(|(index, gamut)| {
+            html! {
+                <>
+                    <input
+                        type="checkbox"
+                        checked={self.show_gamuts[index]}
+                        onchange={ctx.link().callback(move |_| Msg::GamutToggled(index))} />
+                    <label>{gamut.name()}</label>
+                </>
+            }
+        });
+
+        html! {
+            <div>
+                <label>{"Solid: "}</label>
+                <select onchange={on_solid_change}>
+                    { for solids }
+                </select>
+                if self.solid.is_hull() {
+                    { for gamuts }
+                }
+            </div>
+        }
+    }
+
+    fn view_guides(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div>
+                <input
+                    type="checkbox"
+                    checked={self.show_ticks}
+                    onchange={ctx.link().callback(|_| Msg::TicksToggled)} />
+                <label>{"Tick marks every 10%"}</label>
+            </div>
+        }
+    }
+
+    fn view_voxels(&self, ctx: &Context<Self>) -> Html {
+        let on_resolution_input = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::VoxelResolutionChanged(input.value_as_number() as u32)
+        });
+
+        let on_spacing_input = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::VoxelSpacingChanged(input.value_as_number() as f32)
+        });
+
+        html! {
+            <div>
+                <input
+                    type="checkbox"
+                    checked={self.show_voxels}
+                    onchange={ctx.link().callback(|_| Msg::VoxelsToggled)} />
+                <label>{"Voxels"}</label>
+                if self.show_voxels {
+                    <label>{format!(" N = {} ", self.voxels.resolution)}</label>
+                    <input
+                        type="range"
+                        min={MIN_RESOLUTION.to_string()}
+                        max={MAX_RESOLUTION.to_string()}
+                        step="1"
+                        value={self.voxels.resolution.to_string()}
+                        oninput={on_resolution_input} />
+                    <label>{" Explode "}</label>
+                    <input
+                        type="range"
+                        min="0"
+                        max={MAX_SPACING.to_string()}
+                        step="0.05"
+                        value={self.voxels.spacing.to_string()}
+                        oninput={on_spacing_input} />
+                    <input
+                        type="checkbox"
+                        checked={self.voxels.cutaway}
+                        onchange={ctx.link().callback(|_| Msg::CutawayToggled)} />
+                    <label>{"Cutaway (scroll over the cross-section to move the plane)"}</label>
+                }
+            </div>
+        }
+    }
+
+    fn view_export(&self, ctx: &Context<Self>) -> Html {
+        let on_width_change = ctx.link().callback(|event: Event| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::ExportWidthChanged(input.value_as_number().max(0.0) as u32)
+        });
+        let max_width = self.max_export_width();
+
+        html! {
+            <div>
+                <label>{"Export: "}</label>
+                <input
+                    type="number"
+                    min={MIN_EXPORT_WIDTH.to_string()}
+                    max={max_width.to_string()}
+                    value={self.export_width.to_string()}
+                    onchange={on_width_change} />
+                <label>{format!(" px wide (up to {}) ", max_width)}</label>
+                <input
+                    type="checkbox"
+                    checked={self.export_transparent}
+                    onchange={ctx.link().callback(|_| Msg::ExportTransparentToggled)} />
+                <label>{"Transparent background"}</label>
+                <button
+                    class="btn btn-sm btn-outline-secondary"
+                    onclick={ctx.link().callback(|_| Msg::Export(ExportCanvas::Cube))}>
+                    {"Export cube"}
+                </button>
+                <button
+                    class="btn btn-sm btn-outline-secondary"
+                    onclick={ctx.link().callback(|_| Msg::Export(ExportCanvas::CrossSection))}>
+                    {"Export cross-section"}
+                </button>
+            </div>
+        }
     }
 }
 
+// Position inside the cube mesh for a color, matching the per-vertex colors of the cube
+pub fn rgb_to_cube_position((r, g, b): (f32, f32, f32)) -> Vec3 {
+    Vec3::new(b - 0.5, r - 0.5, g - 0.5)
+}
+
 impl Component for Cube {
     type Message = Msg;
     type Properties = Props;
 
     fn create(ctx: &Context<Self>) -> Self {
-        Self::new()
+        let mut cube = Self::new();
+        cube.animation = Some(AnimationLoop::new(ctx.link().callback(Msg::Frame)));
+
+        cube
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let onmousemove_callback = ctx.props().onmousemove.clone();
-        let onmousedown_callback = ctx.props().onmousedown.clone();
-        let onmouseup_callback = ctx.props().onmouseup.clone();
+        let onwheel_callback = ctx.props().onwheel.clone();
+
+        let labels =
+            self.solid
+                .landmarks()
+                .into_iter()
+                .zip(&self.labels)
+                .map(|((name, _), label)| {
+                    html! {
+                        <span ref={label.clone()} style={LABEL_STYLE}>{name}</span>
+                    }
+                });
 
         html! {
             <div>
+            <div style="display: flex; gap: 8px;">
+                <div style={format!("{} position: relative;", CANVAS_STYLE)}>
                 <canvas
-                    width="400"
-                    height="300"
+                    style="display: block; width: 100%; height: 100%; touch-action: none;"
                     ref={self.canvas.clone()}
-                    onmousemove={onmousemove_callback}
-                    onmousedown={onmousedown_callback}
-                    onmouseup={onmouseup_callback.clone()}
-                    onmouseleave={onmouseup_callback.clone()}
+                    onpointerdown={ctx.props().onpointerdown.clone()}
+                    onpointermove={ctx.props().onpointermove.clone()}
+                    onpointerup={ctx.props().onpointerup.clone()}
+                    onpointercancel={ctx.props().onpointerup.clone()}
+                    onwheel={move |e: WheelEvent| {
+                        e.prevent_default();
+                        onwheel_callback.emit(e)
+                    }}
                 />
-                <canvas 
-                    width="400"
-                    height="300"
+                { for labels }
+                </div>
+                <canvas
+                    style={CANVAS_STYLE}
                     ref={self.crosssection.clone()}
                     onwheel={ctx.link().callback(|e: WheelEvent| {
                         e.prevent_default();
@@ -156,6 +1471,18 @@ impl Component for Cube {
                     })}
                 />
             </div>
+            {self.view_status()}
+            <small class="text-muted">{self.stats.summary.clone()}</small>
+            {self.view_solid(ctx)}
+            // Edges, ticks and voxels follow a cube's three coordinates
+            if self.solid.is_cube() {
+                {self.view_guides(ctx)}
+                if !matches!(self.backend, Some(Backend::Software(_))) {
+                    {self.view_voxels(ctx)}
+                }
+            }
+            {self.view_export(ctx)}
+            </div>
         }
     }
 
@@ -163,341 +1490,375 @@ impl Component for Cube {
         match msg {
             Msg::PosChanged(pos) => {
                 self.crossection_pos += pos;
+                // Keeps the slice plane within reach of the cube
+                self.crossection_pos.z = self.crossection_pos.z.clamp(-1.0, 1.0);
+                self.dirty = true;
+
+                false
+            }
+            Msg::SolidChanged(solid) => {
+                self.solid = solid;
+                self.dirty = true;
 
                 true
             }
-        }
-    }
+            Msg::GamutToggled(index) => {
+                self.show_gamuts[index] = !self.show_gamuts[index];
+                self.dirty = true;
 
-    fn changed(&mut self, ctx: &Context<Self>) -> bool {
-        const ROTATION_SPEED: f32 = 0.02;
+                true
+            }
+            Msg::Resized => {
+                let canvas = self.canvas.cast::<HtmlCanvasElement>().unwrap();
+                let crosssection = self.crosssection.cast::<HtmlCanvasElement>().unwrap();
 
-        self.rotate(
-            ctx.props().x_rotation * ROTATION_SPEED,
-            Vec3::new(0.0, 1.0, 0.0),
-        );
-        self.rotate(
-            ctx.props().y_rotation * ROTATION_SPEED,
-            Vec3::new(1.0, 0.0, 0.0),
-        );
+                // Resizing clears the canvases, so they're redrawn either way
+                fit_canvas_to_display(&canvas);
+                fit_canvas_to_display(&crosssection);
+                ctx.props().onresize.emit(display_aspect(&canvas));
+                self.dirty = true;
 
-        true
-    }
+                false
+            }
+            Msg::Frame(time) => {
+                let options = self.view_options();
+                let backend = match &mut self.backend {
+                    Some(backend) => backend,
+                    None => return false,
+                };
+
+                let scene = Scene::new(ctx.props());
+                let (draw_ms, drawn) = if self.dirty || self.drawn_scene.as_ref() != Some(&scene) {
+                    // Time spent issuing GL calls, the GPU may finish later
+                    let start = now();
+                    let drawn = backend.draw(&scene, &options);
+                    self.position_labels(&scene);
+                    self.drawn_scene = Some(scene);
+                    self.dirty = false;
+
+                    (Some(now() - start), Some(drawn.err()))
+                } else {
+                    (None, None)
+                };
+
+                // Only the stats line and the error panel need the DOM
+                let stats_changed = self.stats.record(time, draw_ms);
+                match drawn {
+                    Some(error) if error != self.error => {
+                        if let Some(error) = &error {
+                            log::error!("{}", error);
+                        }
+                        self.error = error;
+
+                        true
+                    }
+                    _ => stats_changed,
+                }
+            }
+            Msg::ExportWidthChanged(width) => {
+                self.export_width = width.clamp(MIN_EXPORT_WIDTH, self.max_export_width());
 
-    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
-        if first_render {
-            let canvas = self.canvas.cast::<web_sys::HtmlCanvasElement>().unwrap();
-            let gl = canvas
-                .get_context("webgl2")
-                .unwrap()
-                .unwrap()
-                .dyn_into::<WebGl2RenderingContext>()
-                .unwrap();
-
-            gl.enable(WebGl2RenderingContext::DEPTH_TEST);
-            self.gl = Some(gl);
-            let gl = self.gl.as_ref().unwrap();
-
-            let vert_shader =
-                compile_shader(&gl, VERT_SHADER, WebGl2RenderingContext::VERTEX_SHADER).unwrap();
-            let frag_shader =
-                compile_shader(&gl, FRAG_SHADER, WebGl2RenderingContext::FRAGMENT_SHADER).unwrap();
-            let shader_program = link_program(&gl, &vert_shader, &frag_shader).unwrap();
-
-            let va = gl.create_vertex_array();
-
-            gl.bind_vertex_array(va.as_ref());
-            // Cube vertices with RGB colors
-            let vertices = Float32Array::from(
-                [
-                    // front
-                    -0.5, -0.5, 0.5, 0.0, 1.0, 0.0, // bottom left
-                    0.5, -0.5, 0.5, 0.0, 1.0, 1.0, // bottom right
-                    0.5, 0.5, 0.5, 1.0, 1.0, 1.0, // top right
-                    -0.5, 0.5, 0.5, 1.0, 1.0, 0.0, // top left
-                    // back
-                    -0.5, -0.5, -0.5, 0.0, 0.0, 0.0, // bottom left
-                    0.5, -0.5, -0.5, 0.0, 0.0, 1.0, // bottom right
-                    0.5, 0.5, -0.5, 1.0, 0.0, 1.0, // top right
-                    -0.5, 0.5, -0.5, 1.0, 0.0, 0.0, // top left
-                ]
-                .as_slice(),
-            );
-            let buffer = gl.create_buffer();
-            gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, buffer.as_ref());
-            gl.buffer_data_with_array_buffer_view(
-                WebGl2RenderingContext::ARRAY_BUFFER,
-                &vertices,
-                WebGl2RenderingContext::STATIC_DRAW,
-            );
+                true
+            }
+            Msg::ExportTransparentToggled => {
+                self.export_transparent = !self.export_transparent;
 
-            // Create index buffer and fill in with cube index data
-            let indices = Uint32Array::from(
-                [
-                    0, 1, 2, 2, 3, 0, // front
-                    4, 5, 6, 6, 7, 4, // back
-                    0, 4, 7, 7, 3, 0, // left
-                    1, 5, 6, 6, 2, 1, // right
-                    3, 2, 6, 6, 7, 3, // top
-                    0, 1, 5, 5, 4, 0, // bottom
-                ]
-                .as_slice(),
-            );
+                true
+            }
+            Msg::Export(canvas) => {
+                let options = self.view_options();
+                let backend = match &mut self.backend {
+                    Some(backend) => backend,
+                    None => return false,
+                };
+
+                let name = match canvas {
+                    ExportCanvas::Cube => "cube",
+                    ExportCanvas::CrossSection => "cross-section",
+                };
+                let exported = backend
+                    .export(
+                        &Scene::new(ctx.props()),
+                        canvas,
+                        self.export_width,
+                        self.export_transparent,
+                        &options,
+                    )
+                    .map_err(|error| error.to_string())
+                    .and_then(|(pixels, width, height)| {
+                        download_png(&pixels, width, height, &format!("{}.png", name))
+                    });
+                // Shown until an export works
+                let error = exported
+                    .err()
+                    .map(|error| format!("Couldn't export the {} view: {}", name, error));
+                if let Some(error) = &error {
+                    log::error!("{}", error);
+                }
+                let changed = error != self.export_error;
+                self.export_error = error;
+
+                changed
+            }
+            Msg::TicksToggled => {
+                self.show_ticks = !self.show_ticks;
+                self.dirty = true;
 
-            let index_buffer = gl.create_buffer();
-            gl.bind_buffer(
-                WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
-                index_buffer.as_ref(),
-            );
-            gl.buffer_data_with_array_buffer_view(
-                WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
-                &indices,
-                WebGl2RenderingContext::STATIC_DRAW,
-            );
+                true
+            }
+            Msg::VoxelsToggled => {
+                self.show_voxels = !self.show_voxels;
+                self.dirty = true;
 
-            // Set up vertex attributes
-            let position_attribute_location = gl.get_attrib_location(&shader_program, "a_position");
-            gl.enable_vertex_attrib_array(position_attribute_location as u32);
-            gl.vertex_attrib_pointer_with_i32(
-                position_attribute_location as u32,
-                3,
-                WebGl2RenderingContext::FLOAT,
-                false,
-                24,
-                0,
-            );
+                true
+            }
+            Msg::VoxelResolutionChanged(resolution) => {
+                self.voxels.resolution = resolution.clamp(MIN_RESOLUTION, MAX_RESOLUTION);
+                self.dirty = true;
 
-            let color_attribute_location = gl.get_attrib_location(&shader_program, "a_color");
-            gl.enable_vertex_attrib_array(color_attribute_location as u32);
-            gl.vertex_attrib_pointer_with_i32(
-                color_attribute_location as u32,
-                3,
-                WebGl2RenderingContext::FLOAT,
-                false,
-                24,
-                12,
-            );
+                true
+            }
+            Msg::VoxelSpacingChanged(spacing) => {
+                self.voxels.spacing = spacing.clamp(0.0, MAX_SPACING);
+                self.dirty = true;
 
-            self.shader_program = Some(shader_program);
-            self.va = va;
-
-            let crosssection = self.crosssection.cast::<web_sys::HtmlCanvasElement>().unwrap();
-            let crosssection_gl = crosssection
-                .get_context("webgl2")
-                .unwrap()
-                .unwrap()
-                .dyn_into::<WebGl2RenderingContext>()
-                .unwrap();
-
-            self.crosssection_ctx = Some(crosssection_gl);
-            
-            let gl = self.crosssection_ctx.as_ref().unwrap();
-            let vert_shader =
-                compile_shader(&gl, CROSS_VERT_SHADER, WebGl2RenderingContext::VERTEX_SHADER).unwrap();
-            let frag_shader =
-                compile_shader(&gl, CROSS_FRAG_SHADER, WebGl2RenderingContext::FRAGMENT_SHADER).unwrap();
-            let shader_program = link_program(&gl, &vert_shader, &frag_shader).unwrap();
-
-            let crosssection_va = gl.create_vertex_array();
-            gl.bind_vertex_array(crosssection_va.as_ref());
-            // Square vertices with RGB colors
-            let vertices = Float32Array::from(
-                [
-                    // front
-                    -0.5, -0.5, 0.0, 1.0, 0.0, // bottom left
-                    0.5, -0.5, 0.0, 1.0, 1.0, // bottom right
-                    0.5, 0.5, 1.0, 1.0, 1.0, // top right
-                    -0.5, 0.5, 1.0, 1.0, 0.0, // top left
-                ]
-                .as_slice(),
-            );
+                true
+            }
+            Msg::CutawayToggled => {
+                self.voxels.cutaway = !self.voxels.cutaway;
+                self.dirty = true;
 
-            let buffer = gl.create_buffer();
-            gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, buffer.as_ref());
-            gl.buffer_data_with_array_buffer_view(
-                WebGl2RenderingContext::ARRAY_BUFFER,
-                &vertices,
-                WebGl2RenderingContext::STATIC_DRAW,
-            );
+                true
+            }
+            Msg::CreateBackend => {
+                self.create_backend();
 
-            // Create index buffer and fill in with cube index data
-            let indices = Uint32Array::from(
-                [
-                    0, 1, 2, 2, 3, 0, // front
-                ]
-                .as_slice(),
-            );
+                true
+            }
+            Msg::ContextLost => {
+                self.lost_contexts += 1;
+                // Everything made with the lost context is gone
+                self.backend = None;
 
-            let index_buffer = gl.create_buffer();
-            gl.bind_buffer(
-                WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
-                index_buffer.as_ref(),
-            );
+                true
+            }
+            Msg::ContextRestored => {
+                self.lost_contexts = self.lost_contexts.saturating_sub(1);
+                if self.lost_contexts == 0 {
+                    self.create_backend();
+                }
 
-            gl.buffer_data_with_array_buffer_view(
-                WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
-                &indices,
-                WebGl2RenderingContext::STATIC_DRAW,
-            );
+                true
+            }
+        }
+    }
 
-            // Set up vertex attributes
-            let position_attribute_location = gl.get_attrib_location(&shader_program, "a_position");
-            gl.enable_vertex_attrib_array(position_attribute_location as u32);
-            gl.vertex_attrib_pointer_with_i32(
-                position_attribute_location as u32,
-                2,
-                WebGl2RenderingContext::FLOAT,
-                false,
-                20,
-                0,
-            );
+    // Prop changes are picked up by the next frame, the DOM itself never depends on them
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        false
+    }
 
-            let color_attribute_location = gl.get_attrib_location(&shader_program, "a_color");
-            gl.enable_vertex_attrib_array(color_attribute_location as u32);
-            gl.vertex_attrib_pointer_with_i32(
-                color_attribute_location as u32,
-                3,
-                WebGl2RenderingContext::FLOAT,
-                false,
-                20,
-                8,
-            );
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        if first_render {
+            let canvas = self.canvas.cast::<HtmlCanvasElement>().unwrap();
+            let crosssection = self.crosssection.cast::<HtmlCanvasElement>().unwrap();
+
+            // From update, so an error panel gets rendered
+            ctx.link().send_message(Msg::CreateBackend);
+
+            let link = ctx.link();
+            for element in [&canvas, &crosssection] {
+                let lost = link.callback(|_| Msg::ContextLost);
+                // The context is only restored if the default of the lost event is prevented
+                self.context_listeners.push(EventListener::new_with_options(
+                    element,
+                    "webglcontextlost",
+                    EventListenerOptions::enable_prevent_default(),
+                    move |event| {
+                        event.prevent_default();
+                        lost.emit(());
+                    },
+                ));
+
+                let restored = link.callback(|_| Msg::ContextRestored);
+                self.context_listeners.push(EventListener::new(
+                    element,
+                    "webglcontextrestored",
+                    move |_| restored.emit(()),
+                ));
+            }
 
-            self.crosssection_va = crosssection_va;
-            self.crosssection_shader_program = Some(shader_program);
+            self.resize_watcher = Some(ResizeWatcher::new(
+                &[canvas.into(), crosssection.into()],
+                ctx.link().callback(|_| Msg::Resized),
+            ));
         }
+    }
 
-        let gl = self.gl.as_ref().unwrap();
-        let shader_program = self.shader_program.as_ref().unwrap();
-        gl.use_program(Some(shader_program));
+    fn destroy(&mut self, ctx: &Context<Self>) {}
+}
 
-        // Set up uniforms
-        let model_uniform_location = gl.get_uniform_location(&shader_program, "u_model");
-        let view_uniform_location = gl.get_uniform_location(&shader_program, "u_view");
-        let projection_uniform_location = gl.get_uniform_location(&shader_program, "u_projection");
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
 
-        gl.uniform_matrix4fv_with_f32_array(
-            model_uniform_location.as_ref(),
-            false,
-            &self.view.as_slice(),
-        );
+    use nalgebra::UnitQuaternion;
 
-        gl.uniform_matrix4fv_with_f32_array(
-            view_uniform_location.as_ref(),
-            false,
-            &ctx.props().view.as_slice(),
-        );
+    use super::*;
+    use crate::camera::Camera;
 
-        gl.uniform_matrix4fv_with_f32_array(
-            projection_uniform_location.as_ref(),
-            false,
-            &ctx.props().projection.as_slice(),
-        );
+    const WIDTH: u32 = 160;
+    const HEIGHT: u32 = 120;
+    // Per channel difference allowed for rounding differences between platforms
+    const TOLERANCE: u8 = 2;
 
-        gl.clear_color(0.0, 0.0, 0.0, 1.0);
-        gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
-        gl.clear(WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+    fn golden_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{}.ppm", name))
+    }
 
-        gl.bind_vertex_array(self.va.as_ref());
-        gl.draw_elements_with_i32(
-            WebGl2RenderingContext::TRIANGLES,
-            36,
-            WebGl2RenderingContext::UNSIGNED_INT,
-            0,
-        );
+    // Binary PPM, any image viewer opens it and it needs no dependencies
+    fn encode_ppm(rgba: &[u8]) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", WIDTH, HEIGHT).into_bytes();
+        for pixel in rgba.chunks_exact(4) {
+            ppm.extend_from_slice(&pixel[..3]);
+        }
+        ppm
+    }
+
+    // Compares against the golden image, UPDATE_GOLDEN=1 cargo test rewrites them instead
+    fn assert_matches_golden(name: &str, target: &SoftwareTarget) {
+        let path = golden_path(name);
+        let actual = encode_ppm(&target.pixels());
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &actual).unwrap();
+            return;
+        }
 
-        gl.bind_vertex_array(None);
-
-        // draw crossection of cube
-        let crosssection_gl = self.crosssection_ctx.as_ref().unwrap();
-        let shader_program = self.crosssection_shader_program.as_ref().unwrap();
-        crosssection_gl.use_program(Some(shader_program));
-        
-        let u_view = crosssection_gl.get_uniform_location(&shader_program, "u_view");
-        crosssection_gl.uniform_matrix4fv_with_f32_array(
-            u_view.as_ref(),
-            false,
-            &self.view.as_slice(),
+        let expected = std::fs::read(&path).unwrap_or_else(|error| {
+            panic!(
+                "can't read {}: {}, run with UPDATE_GOLDEN=1 to create it",
+                path.display(),
+                error
+            )
+        });
+        assert_eq!(
+            expected.len(),
+            actual.len(),
+            "{} has a different size",
+            name
         );
 
-        let u_projection = crosssection_gl.get_uniform_location(&shader_program, "u_projection");
-        let proj = nalgebra_glm::ortho(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
-        crosssection_gl.uniform_matrix4fv_with_f32_array(
-            u_projection.as_ref(),
-            false,
-            &proj.as_slice(),
+        let differing = expected
+            .iter()
+            .zip(&actual)
+            .filter(|(e, a)| e.abs_diff(**a) > TOLERANCE)
+            .count();
+        assert_eq!(
+            differing,
+            0,
+            "{} channels differ from {}",
+            differing,
+            path.display()
         );
+    }
 
-        let u_model = crosssection_gl.get_uniform_location(&shader_program, "u_model");
-        crosssection_gl.uniform_matrix4fv_with_f32_array(
-            u_model.as_ref(),
-            false,
-            &self.view.as_slice(),
+    fn camera() -> Camera {
+        let mut camera = Camera::new();
+        camera.set_aspect(WIDTH as f32 / HEIGHT as f32);
+        camera
+    }
+
+    fn model() -> Mat4x4 {
+        UnitQuaternion::from_euler_angles(0.5, 0.7, 0.0).to_homogeneous()
+    }
+
+    #[test]
+    fn cube_matches_golden() {
+        let camera = camera();
+        let mut target = SoftwareTarget::new(WIDTH, HEIGHT);
+
+        target.begin_frame((0.0, 0.0, 0.0, 1.0));
+        draw_cube_software(
+            &mut target,
+            Solid::RgbCube,
+            &model(),
+            &camera.calculate_view_matrix(),
+            &camera.calculate_projection_matrix(),
+            1.0,
         );
 
-        crosssection_gl.clear_color(0.0, 0.0, 0.0, 1.0);
-        crosssection_gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
+        assert_matches_golden("cube", &target);
+    }
 
-        crosssection_gl.bind_vertex_array(self.crosssection_va.as_ref());
-        crosssection_gl.draw_elements_with_i32(
-            WebGl2RenderingContext::TRIANGLES,
-            6,
-            WebGl2RenderingContext::UNSIGNED_INT,
-            0,
+    #[test]
+    fn translucent_cube_matches_golden() {
+        let camera = camera();
+        let mut target = SoftwareTarget::new(WIDTH, HEIGHT);
+
+        target.begin_frame((0.0, 0.0, 0.0, 1.0));
+        draw_cube_software(
+            &mut target,
+            Solid::RgbCube,
+            &model(),
+            &camera.calculate_view_matrix(),
+            &camera.calculate_projection_matrix(),
+            CUBE_ALPHA_WITH_POINTS,
         );
 
+        assert_matches_golden("cube_translucent", &target);
     }
 
-    fn destroy(&mut self, ctx: &Context<Self>) {}
-}
+    #[test]
+    fn crosssection_matches_golden() {
+        let mut target = SoftwareTarget::new(WIDTH, HEIGHT);
+
+        target.begin_frame((0.0, 0.0, 0.0, 1.0));
+        draw_crosssection_software(
+            &mut target,
+            &ColorVolume::new(Solid::RgbCube),
+            &camera().calculate_view_matrix(),
+            &model(),
+            0.1,
+        );
 
-fn compile_shader(
-    gl: &WebGl2RenderingContext,
-    source: &str,
-    shader_type: u32,
-) -> Result<WebGlShader, String> {
-    let shader = gl
-        .create_shader(shader_type)
-        .ok_or_else(|| String::from("Unable to create shader object"))?;
-
-    gl.shader_source(&shader, source);
-    gl.compile_shader(&shader);
-
-    if gl
-        .get_shader_parameter(&shader, WebGl2RenderingContext::COMPILE_STATUS)
-        .as_bool()
-        .unwrap_or(false)
-    {
-        Ok(shader)
-    } else {
-        Err(gl
-            .get_shader_info_log(&shader)
-            .unwrap_or_else(|| String::from("Unknown error creating shader")))
+        assert_matches_golden("crosssection", &target);
     }
-}
 
-fn link_program(
-    gl: &WebGl2RenderingContext,
-    vert_shader: &WebGlShader,
-    frag_shader: &WebGlShader,
-) -> Result<WebGlProgram, String> {
-    let program = gl
-        .create_program()
-        .ok_or_else(|| String::from("Unable to create shader object"))?;
-
-    gl.attach_shader(&program, vert_shader);
-    gl.attach_shader(&program, frag_shader);
-    gl.link_program(&program);
-
-    if gl
-        .get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS)
-        .as_bool()
-        .unwrap_or(false)
-    {
-        Ok(program)
-    } else {
-        Err(gl
-            .get_program_info_log(&program)
-            .unwrap_or_else(|| String::from("Unknown error creating program object")))
+    #[test]
+    fn solids_match_golden() {
+        let camera = camera();
+
+        // The RGB cube has its own goldens above
+        for solid in &SOLIDS[1..] {
+            let solid = *solid;
+            let name = solid.name().to_lowercase().replace(' ', "_");
+
+            let mut target = SoftwareTarget::new(WIDTH, HEIGHT);
+            target.begin_frame((0.0, 0.0, 0.0, 1.0));
+            draw_cube_software(
+                &mut target,
+                solid,
+                &model(),
+                &camera.calculate_view_matrix(),
+                &camera.calculate_projection_matrix(),
+                1.0,
+            );
+            assert_matches_golden(&format!("solid_{}", name), &target);
+
+            let mut target = SoftwareTarget::new(WIDTH, HEIGHT);
+            target.begin_frame((0.0, 0.0, 0.0, 1.0));
+            draw_crosssection_software(
+                &mut target,
+                &ColorVolume::new(solid),
+                &camera.calculate_view_matrix(),
+                &model(),
+                0.0,
+            );
+            assert_matches_golden(&format!("crosssection_{}", name), &target);
+        }
     }
 }
@@ -0,0 +1,480 @@
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::color::{cmyk_to_rgb, rgb_to_str};
+use crate::color_picker::CMYK;
+use crate::separation::{ink_amount, ink_color, plate_name, PLATES};
+
+const PLOT_SIZE: f32 = 200.0;
+
+// ISO 12647-2:2013 tone value increase curves A to E, named by the standard and tabulated by
+// their increase at 50% tone
+pub const ISO_12647_PRESETS: [(&str, f32); 5] = [
+    ("A", 0.13),
+    ("B", 0.16),
+    ("C", 0.19),
+    ("D", 0.22),
+    ("E", 0.25),
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Preset {
+    None,
+    // Index into ISO_12647_PRESETS
+    Iso(usize),
+    Custom,
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct DotGainCurve {
+    // Nominal tone and its tone value increase, sorted by tone
+    points: Vec<(f32, f32)>,
+}
+
+impl DotGainCurve {
+    pub fn none() -> Self {
+        Self { points: Vec::new() }
+    }
+
+    // The tabulated increase at 50% of an ISO 12647-2 curve, interpolated to no increase at
+    // paper and solid
+    pub fn iso(increase_at_50: f32) -> Self {
+        Self {
+            points: vec![(0.5, increase_at_50)],
+        }
+    }
+
+    // A smooth curve of our own, with no increase at paper and solid and the given increase at
+    // 50%, sampled every 10%
+    pub fn custom(increase_at_50: f32) -> Self {
+        let spline = |tone: f32| {
+            let t = tone.min(1.0 - tone);
+            increase_at_50 * (3.0 * t - 4.0 * t * t * t)
+        };
+
+        Self {
+            points: (1..10)
+                .map(|step| {
+                    let tone = step as f32 / 10.0;
+                    (tone, spline(tone))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn preset(&self) -> Preset {
+        if self.points.is_empty() {
+            return Preset::None;
+        }
+
+        ISO_12647_PRESETS
+            .iter()
+            .position(|(_, increase)| *self == Self::iso(*increase))
+            .map_or(Preset::Custom, Preset::Iso)
+    }
+
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    pub fn add_point(&mut self, tone: f32, increase: f32) {
+        let tone = tone.clamp(0.0, 1.0);
+        self.points.retain(|(t, _)| (t - tone).abs() > 0.01);
+        self.points.push((tone, increase));
+        self.points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+
+    pub fn remove_point(&mut self, index: usize) {
+        if index < self.points.len() {
+            self.points.remove(index);
+        }
+    }
+
+    pub fn increase(&self, tone: f32) -> f32 {
+        // Paper white and solid ink never gain
+        let mut previous = (0.0, 0.0);
+        for &(t, increase) in self.points.iter().chain([(1.0, 0.0)].iter()) {
+            if tone <= t {
+                let span = (t - previous.0).max(f32::EPSILON);
+                return previous.1 + (increase - previous.1) * (tone - previous.0) / span;
+            }
+            previous = (t, increase);
+        }
+
+        0.0
+    }
+
+    // Tone printed on press for a nominal tone in the file
+    pub fn apply(&self, tone: f32) -> f32 {
+        (tone + self.increase(tone)).clamp(0.0, 1.0)
+    }
+}
+
+// One curve per plate, in the order of PLATES
+pub type DotGainCurves = [DotGainCurve; 4];
+
+pub fn apply_dot_gain(
+    (c, m, y, k): (f32, f32, f32, f32),
+    curves: &DotGainCurves,
+) -> (f32, f32, f32, f32) {
+    (
+        curves[0].apply(c),
+        curves[1].apply(m),
+        curves[2].apply(y),
+        curves[3].apply(k),
+    )
+}
+
+pub fn cmyk_to_rgb_with_gain(
+    cmyk: (f32, f32, f32, f32),
+    curves: &DotGainCurves,
+) -> (f32, f32, f32) {
+    cmyk_to_rgb(apply_dot_gain(cmyk, curves))
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub cmyk: (f32, f32, f32, f32),
+    pub curves: DotGainCurves,
+    pub onchange: Callback<DotGainCurves>,
+}
+
+pub enum Msg {
+    PlateSelected(usize),
+    PresetSelected(Preset),
+    CustomGainChanged(f32),
+    PointAdded(f32, f32),
+    PointRemoved(usize),
+}
+
+pub struct DotGainPanel {
+    plate: usize,
+    tone_input: NodeRef,
+    increase_input: NodeRef,
+}
+
+impl DotGainPanel {
+    fn view_plot(&self, ctx: &Context<Self>) -> Html {
+        let curves = &ctx.props().curves;
+        let plots = curves
+            .iter()
+            .zip(PLATES)
+            .enumerate()
+            .map(|(index, (curve, plate))| {
+                let points = (0..=50)
+                    .map(|step| {
+                        let tone = step as f32 / 50.0;
+                        format!(
+                            "{:.1},{:.1}",
+                            tone * PLOT_SIZE,
+                            (1.0 - curve.apply(tone)) * PLOT_SIZE
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let color = match plate {
+                    CMYK::Key => "#888888".to_string(),
+                    _ => rgb_to_str(ink_color(plate)),
+                };
+                let width = if index == self.plate { "3" } else { "1" };
+
+                html! {
+                    <polyline points={points} fill="none" stroke={color} stroke-width={width} />
+                }
+            });
+
+        let curve = &curves[self.plate];
+        let handles = curve.points().iter().map(|(tone, _)| {
+            let printed = curve.apply(*tone);
+
+            html! {
+                <circle
+                    cx={(tone * PLOT_SIZE).to_string()}
+                    cy={((1.0 - printed) * PLOT_SIZE).to_string()}
+                    r="4"
+                    fill="white"
+                    stroke="black" />
+            }
+        });
+
+        // Clicking the plot places a point at that nominal tone and printed tone, measured from
+        // the svg since event offsets are relative to whichever curve or handle was hit
+        let on_plot_click = ctx.link().batch_callback(|event: MouseEvent| {
+            let svg = event.current_target()?.unchecked_into::<Element>();
+            let rect = svg.get_bounding_client_rect();
+            let tone = (event.client_x() as f64 - rect.left()) as f32 / PLOT_SIZE;
+            let printed = 1.0 - (event.client_y() as f64 - rect.top()) as f32 / PLOT_SIZE;
+
+            Some(Msg::PointAdded(tone, printed - tone))
+        });
+
+        let size = PLOT_SIZE.to_string();
+        html! {
+            <svg
+                width={size.clone()}
+                height={size.clone()}
+                style="background-color: white; border: 1px solid #ccc;"
+                onclick={on_plot_click}>
+                <line x1="0" y1={size.clone()} x2={size.clone()} y2="0" stroke="#ddd" />
+                { for plots }
+                { for handles }
+            </svg>
+        }
+    }
+
+    fn view_points(&self, ctx: &Context<Self>) -> Html {
+        let curve = &ctx.props().curves[self.plate];
+        let points = curve
+            .points()
+            .iter()
+            .enumerate()
+            .map(|(index, (tone, increase))| {
+                html! {
+                    <li>
+                        {format!("{:.0}% +{:.1}% ", tone * 100.0, increase * 100.0)}
+                        <button
+                            class="btn btn-sm btn-outline-secondary"
+                            onclick={ctx.link().callback(move |_| Msg::PointRemoved(index))}>
+                            {"×"}
+                        </button>
+                    </li>
+                }
+            });
+
+        let on_add = {
+            let tone_input = self.tone_input.clone();
+            let increase_input = self.increase_input.clone();
+
+            ctx.link().callback(move |_: MouseEvent| {
+                let value = |input: &NodeRef| {
+                    input
+                        .cast::<HtmlInputElement>()
+                        .map(|input| input.value_as_number())
+                        .filter(|value| !value.is_nan())
+                        .unwrap_or(0.0) as f32
+                        / 100.0
+                };
+
+                Msg::PointAdded(value(&tone_input), value(&increase_input))
+            })
+        };
+
+        html! {
+            <div>
+                <ul class="list-unstyled">
+                    { for points }
+                </ul>
+                <input
+                    ref={self.tone_input.clone()}
+                    type="number"
+                    min="0"
+                    max="100"
+                    placeholder="Tone %" />
+                <input
+                    ref={self.increase_input.clone()}
+                    type="number"
+                    min="-50"
+                    max="50"
+                    placeholder="Gain %" />
+                <button class="btn btn-sm btn-secondary" onclick={on_add}>{"Add point"}</button>
+            </div>
+        }
+    }
+}
+
+impl Component for DotGainPanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            plate: 0,
+            tone_input: NodeRef::default(),
+            increase_input: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let on_plate_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            Msg::PlateSelected(select.parse().unwrap_or(0))
+        });
+
+        let on_preset_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            Msg::PresetSelected(match select.as_str() {
+                "custom" => Preset::Custom,
+                index => index.parse().map_or(Preset::None, Preset::Iso),
+            })
+        });
+
+        let on_custom_change = ctx.link().callback(|event: Event| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::CustomGainChanged(input.value_as_number() as f32 / 100.0)
+        });
+
+        let plates = PLATES.iter().enumerate().map(|(index, plate)| {
+            html! {
+                <option value={index.to_string()} selected={index == self.plate}>
+                    {plate_name(*plate)}
+                </option>
+            }
+        });
+
+        let curve = &ctx.props().curves[self.plate];
+        let preset = curve.preset();
+        let presets = ISO_12647_PRESETS
+            .iter()
+            .enumerate()
+            .map(|(index, (name, increase))| {
+                html! {
+                    <option value={index.to_string()} selected={preset == Preset::Iso(index)}>
+                        {format!("ISO 12647-2 {} ({:.0}% at 50%)", name, increase * 100.0)}
+                    </option>
+                }
+            });
+        let custom_input = if preset == Preset::Custom {
+            html! {
+                <input
+                    type="number"
+                    min="0"
+                    max="50"
+                    value={format!("{:.0}", curve.increase(0.5) * 100.0)}
+                    title="Increase at 50%"
+                    onchange={on_custom_change} />
+            }
+        } else {
+            html! {}
+        };
+
+        let Props { cmyk, curves, .. } = ctx.props();
+        let cmyk = *cmyk;
+        let before = rgb_to_str(cmyk_to_rgb(cmyk));
+        let after = rgb_to_str(cmyk_to_rgb_with_gain(cmyk, curves));
+        let gained = ink_amount(apply_dot_gain(cmyk, curves), PLATES[self.plate]);
+
+        html! {
+            <div class="container">
+                <div class="row">
+                    <div class="col">
+                        <label>{"Dot gain: "}</label>
+                        <select onchange={on_plate_change}>
+                            { for plates }
+                        </select>
+                        <select onchange={on_preset_change}>
+                            <option value="none" selected={preset == Preset::None}>{"None"}</option>
+                            { for presets }
+                            <option value="custom" selected={preset == Preset::Custom}>
+                                {"Custom"}
+                            </option>
+                        </select>
+                        { custom_input }
+                    </div>
+                </div>
+                <div class="row">
+                    <div class="col-auto">
+                        { self.view_plot(ctx) }
+                    </div>
+                    <div class="col">
+                        { self.view_points(ctx) }
+                    </div>
+                    <div class="col-auto text-center">
+                        <div style={format!("background-color: {}; width: 64px; height: 64px;", before)}></div>
+                        <small>{"Ideal"}</small>
+                    </div>
+                    <div class="col-auto text-center">
+                        <div style={format!("background-color: {}; width: 64px; height: 64px;", after)}></div>
+                        <small>{"On press"}</small>
+                        <div>
+                            <small>
+                                {format!(
+                                    "{} {:.1}% → {:.1}%",
+                                    plate_name(PLATES[self.plate]),
+                                    ink_amount(cmyk, PLATES[self.plate]) * 100.0,
+                                    gained * 100.0
+                                )}
+                            </small>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        // The curves belong to the app, edits come back as new props
+        let mut curves = ctx.props().curves.clone();
+        match msg {
+            Msg::PlateSelected(plate) => {
+                self.plate = plate.min(PLATES.len() - 1);
+
+                return true;
+            }
+            Msg::PresetSelected(preset) => {
+                let curve = &curves[self.plate];
+                curves[self.plate] = match preset {
+                    Preset::None => DotGainCurve::none(),
+                    Preset::Iso(index) => DotGainCurve::iso(ISO_12647_PRESETS[index].1),
+                    // Starts from the current midtone gain, points edited by hand stay as they are
+                    Preset::Custom if curve.preset() == Preset::Custom => return false,
+                    Preset::Custom => DotGainCurve::custom(curve.increase(0.5)),
+                };
+            }
+            Msg::CustomGainChanged(increase) => {
+                if increase.is_nan() {
+                    return false;
+                }
+                curves[self.plate] = DotGainCurve::custom(increase.clamp(0.0, 0.5));
+            }
+            Msg::PointAdded(tone, increase) => {
+                curves[self.plate].add_point(tone, increase);
+            }
+            Msg::PointRemoved(index) => {
+                curves[self.plate].remove_point(index);
+            }
+        }
+        ctx.props().onchange.emit(curves);
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gain_spares_paper_and_solids() {
+        let curve = DotGainCurve::custom(0.16);
+
+        assert_eq!(curve.apply(0.0), 0.0);
+        assert!((curve.apply(0.5) - 0.66).abs() < 1e-6);
+        assert_eq!(curve.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn iso_presets_gain_their_tabulated_value_at_midtone() {
+        for (index, (_, increase)) in ISO_12647_PRESETS.into_iter().enumerate() {
+            let curve = DotGainCurve::iso(increase);
+
+            assert_eq!(curve.increase(0.5), increase);
+            assert_eq!(curve.preset(), Preset::Iso(index));
+        }
+        assert_eq!(DotGainCurve::custom(0.16).preset(), Preset::Custom);
+        assert_eq!(DotGainCurve::none().preset(), Preset::None);
+    }
+
+    #[test]
+    fn no_curve_prints_tones_as_they_are() {
+        let curve = DotGainCurve::none();
+
+        for tone in [0.0, 0.5, 1.0] {
+            assert_eq!(curve.apply(tone), tone);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{window, CanvasRenderingContext2d, HtmlAnchorElement, HtmlCanvasElement, ImageData};
+
+// Width exported images start at, the height follows the canvas's aspect
+pub const DEFAULT_EXPORT_WIDTH: u32 = 1920;
+pub const MIN_EXPORT_WIDTH: u32 = 16;
+// Exports render at this many times their size and get averaged down for smooth edges
+pub const SUPERSAMPLING: u32 = 2;
+
+// Widest export of a canvas this many times wider than tall that keeps both sides within max_size
+pub fn widest_export(aspect: f32, max_size: u32) -> u32 {
+    ((max_size as f32 * aspect.min(1.0)).floor() as u32).max(1)
+}
+
+// Width and height to export a canvas of this aspect at, shrunk until neither side is over
+// max_size, and how many times over it can be supersampled within the same limit
+pub fn export_size(width: u32, aspect: f32, max_size: u32) -> (u32, u32, u32) {
+    let width = width.min(widest_export(aspect, max_size));
+    let height = ((width as f32 / aspect).round() as u32).clamp(1, max_size);
+    let factor = (max_size / width.max(height)).clamp(1, SUPERSAMPLING);
+
+    (width, height, factor)
+}
+
+// Averages factor × factor blocks of pixels. Colors are weighted by alpha so a transparent
+// background doesn't darken the edges of what's drawn over it.
+pub fn downsample(rgba: &[u8], width: u32, height: u32, factor: u32) -> Vec<u8> {
+    let (out_width, out_height) = (width / factor, height / factor);
+    let mut out = Vec::with_capacity((out_width * out_height * 4) as usize);
+
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let mut sum = [0.0f32; 4];
+
+            for sy in y * factor..(y + 1) * factor {
+                for sx in x * factor..(x + 1) * factor {
+                    let index = ((sy * width + sx) * 4) as usize;
+                    let alpha = rgba[index + 3] as f32;
+
+                    for channel in 0..3 {
+                        sum[channel] += rgba[index + channel] as f32 * alpha;
+                    }
+                    sum[3] += alpha;
+                }
+            }
+
+            let alpha = sum[3];
+            for total in &sum[..3] {
+                let value = if alpha > 0.0 { total / alpha } else { 0.0 };
+                out.push(value.round() as u8);
+            }
+            out.push((alpha / (factor * factor) as f32).round() as u8);
+        }
+    }
+
+    out
+}
+
+// Encodes the pixels as a PNG through a scratch canvas and saves it as a download
+pub fn download_png(rgba: &[u8], width: u32, height: u32, filename: &str) -> Result<(), String> {
+    let document = window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| String::from("No document to download from"))?;
+
+    let canvas = document
+        .create_element("canvas")
+        .ok()
+        .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+        .ok_or_else(|| String::from("Couldn't create a canvas to encode with"))?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+
+    let context = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or_else(|| String::from("Couldn't get a 2D context to encode with"))?;
+    let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(rgba), width, height)
+        .map_err(|_| String::from("Couldn't create image data"))?;
+    context
+        .put_image_data(&data, 0.0, 0.0)
+        .map_err(|_| String::from("Couldn't draw image data"))?;
+
+    let url = canvas
+        .to_data_url_with_type("image/png")
+        .map_err(|_| String::from("Couldn't encode the PNG"))?;
+
+    let link = document
+        .create_element("a")
+        .ok()
+        .and_then(|element| element.dyn_into::<HtmlAnchorElement>().ok())
+        .ok_or_else(|| String::from("Couldn't create a download link"))?;
+    link.set_href(&url);
+    link.set_download(filename);
+    link.click();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tall_exports_fit_the_limit_by_their_height() {
+        assert_eq!(widest_export(0.5, 4096), 2048);
+        assert_eq!(export_size(4096, 0.5, 4096), (2048, 4096, 1));
+        assert_eq!(export_size(1000, 0.5, 4096), (1000, 2000, 2));
+        assert_eq!(export_size(4096, 2.0, 4096), (4096, 2048, 1));
+    }
+}
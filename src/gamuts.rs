@@ -0,0 +1,106 @@
+use nalgebra::{Matrix3, Vector3};
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::solids::{cube_face_point, Solid};
+
+// Grid lines across each face of a gamut's cube
+const GRID_LINES: u32 = 6;
+// Segments along each grid line, they curve in perceptual coordinates
+const LINE_SEGMENTS: u32 = 24;
+
+// Linear RGB to XYZ, both gamuts have a D65 white point like sRGB
+const DISPLAY_P3_TO_XYZ: [f32; 9] = [
+    0.4865709, 0.2656677, 0.1982173, //
+    0.2289746, 0.6917385, 0.0792869, //
+    0.0000000, 0.0451134, 1.0439444, //
+];
+const REC_2020_TO_XYZ: [f32; 9] = [
+    0.636958, 0.1446169, 0.168881, //
+    0.2627002, 0.6779981, 0.0593017, //
+    0.0000000, 0.0280727, 1.0609851, //
+];
+const XYZ_TO_LINEAR_SRGB: [f32; 9] = [
+    3.24097, -1.5373832, -0.4986108, //
+    -0.9692436, 1.8759675, 0.0415551, //
+    0.0556301, -0.203977, 1.0569715, //
+];
+
+// Wide gamuts drawn around the sRGB hulls for comparison
+#[derive(Clone, Copy, PartialEq)]
+pub enum Gamut {
+    DisplayP3,
+    Rec2020,
+}
+
+pub const GAMUTS: [Gamut; 2] = [Gamut::DisplayP3, Gamut::Rec2020];
+
+impl Gamut {
+    pub fn name(self) -> &'static str {
+        match self {
+            Gamut::DisplayP3 => "Display P3",
+            Gamut::Rec2020 => "Rec. 2020",
+        }
+    }
+
+    fn line_color(self) -> (f32, f32, f32) {
+        match self {
+            Gamut::DisplayP3 => (1.0, 0.8, 0.3),
+            Gamut::Rec2020 => (0.5, 0.8, 1.0),
+        }
+    }
+
+    // The gamut's own 0..1 RGB as sRGB, outside 0..1 where sRGB can't show it
+    fn to_srgb(self, (r, g, b): (f32, f32, f32)) -> (f32, f32, f32) {
+        let to_xyz = match self {
+            Gamut::DisplayP3 => DISPLAY_P3_TO_XYZ,
+            Gamut::Rec2020 => REC_2020_TO_XYZ,
+        };
+        let to_srgb =
+            Matrix3::from_row_slice(&XYZ_TO_LINEAR_SRGB) * Matrix3::from_row_slice(&to_xyz);
+
+        // The transfer curve only spaces the grid lines, the hull's shape comes from the primaries
+        let linear =
+            to_srgb * Vector3::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+
+        (
+            linear_to_srgb(linear.x),
+            linear_to_srgb(linear.y),
+            linear_to_srgb(linear.z),
+        )
+    }
+}
+
+// A grid over the faces of the gamut's cube as line pairs, placed in a hull solid
+pub fn wireframe_vertices(gamut: Gamut, solid: Solid) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    let (r, g, b) = gamut.line_color();
+    let mut push = |point: (f32, f32, f32)| {
+        let position = solid.position(gamut.to_srgb(point));
+        vertices.extend_from_slice(&[position.x, position.y, position.z, r, g, b]);
+    };
+
+    for axis in 0..3 {
+        for side in [0.0, 1.0] {
+            for line in 0..=GRID_LINES {
+                let across = line as f32 / GRID_LINES as f32;
+
+                // Lines both ways across the face
+                for flip in [false, true] {
+                    for segment in 0..LINE_SEGMENTS {
+                        for along in [segment, segment + 1] {
+                            let along = along as f32 / LINE_SEGMENTS as f32;
+                            let (s, t) = if flip {
+                                (along, across)
+                            } else {
+                                (across, along)
+                            };
+                            push(cube_face_point(axis, side, s, t));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    vertices
+}
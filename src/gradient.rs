@@ -0,0 +1,469 @@
+use std::rc::Rc;
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::canvas::{push_rgba, put_rgba, view_draw_error};
+use crate::color::{
+    clamp_rgb, cmyk_to_rgb, lab_to_rgb, linear_to_srgb, oklab_to_oklch, oklab_to_rgb,
+    oklch_to_oklab, rgb_to_cmyk, rgb_to_lab, rgb_to_oklab, rgb_to_str, srgb_to_linear,
+};
+use crate::point_cloud::point_vertices;
+use crate::renderer::RenderError;
+
+const PREVIEW_WIDTH: u32 = 400;
+const PREVIEW_HEIGHT: u32 = 40;
+const PATH_SAMPLES: usize = 128;
+// Stops exported for spaces CSS can't interpolate in
+const CSS_FALLBACK_STOPS: usize = 16;
+// OKLCH chroma below which a color counts as gray and its hue as missing
+const ACHROMATIC_CHROMA: f32 = 1e-3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HueInterpolation {
+    Shorter,
+    Longer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum InterpolationSpace {
+    Srgb,
+    LinearSrgb,
+    Lab,
+    Oklab,
+    Oklch(HueInterpolation),
+    Cmyk,
+}
+
+impl InterpolationSpace {
+    pub const ALL: [(InterpolationSpace, &'static str); 7] = [
+        (InterpolationSpace::Srgb, "sRGB"),
+        (InterpolationSpace::LinearSrgb, "Linear sRGB"),
+        (InterpolationSpace::Lab, "CIELAB"),
+        (InterpolationSpace::Oklab, "OKLab"),
+        (
+            InterpolationSpace::Oklch(HueInterpolation::Shorter),
+            "OKLCH (shorter hue)",
+        ),
+        (
+            InterpolationSpace::Oklch(HueInterpolation::Longer),
+            "OKLCH (longer hue)",
+        ),
+        (InterpolationSpace::Cmyk, "CMYK"),
+    ];
+
+    // Interpolation method in CSS Color 4 syntax, None if CSS has no such space
+    pub fn css_name(&self) -> Option<&'static str> {
+        match self {
+            InterpolationSpace::Srgb => Some("srgb"),
+            InterpolationSpace::LinearSrgb => Some("srgb-linear"),
+            InterpolationSpace::Lab => Some("lab"),
+            InterpolationSpace::Oklab => Some("oklab"),
+            InterpolationSpace::Oklch(HueInterpolation::Shorter) => Some("oklch"),
+            InterpolationSpace::Oklch(HueInterpolation::Longer) => Some("oklch longer hue"),
+            InterpolationSpace::Cmyk => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    pub rgb: (f32, f32, f32),
+    pub position: f32,
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp3(a: (f32, f32, f32), b: (f32, f32, f32), t: f32) -> (f32, f32, f32) {
+    (lerp(a.0, b.0, t), lerp(a.1, b.1, t), lerp(a.2, b.2, t))
+}
+
+pub fn interpolate(
+    a: (f32, f32, f32),
+    b: (f32, f32, f32),
+    t: f32,
+    space: InterpolationSpace,
+) -> (f32, f32, f32) {
+    let rgb = match space {
+        InterpolationSpace::Srgb => lerp3(a, b, t),
+        InterpolationSpace::LinearSrgb => {
+            let to_linear = |(r, g, b): (f32, f32, f32)| {
+                (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b))
+            };
+            let (r, g, b) = lerp3(to_linear(a), to_linear(b), t);
+
+            (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+        }
+        InterpolationSpace::Lab => lab_to_rgb(lerp3(rgb_to_lab(a), rgb_to_lab(b), t)),
+        InterpolationSpace::Oklab => oklab_to_rgb(lerp3(rgb_to_oklab(a), rgb_to_oklab(b), t)),
+        InterpolationSpace::Oklch(hue) => {
+            let (mut a, mut b) = (
+                oklab_to_oklch(rgb_to_oklab(a)),
+                oklab_to_oklch(rgb_to_oklab(b)),
+            );
+
+            // Grays have no hue, like CSS Color 4 they take the other stop's
+            if a.1 < ACHROMATIC_CHROMA {
+                a.2 = b.2;
+            }
+            if b.1 < ACHROMATIC_CHROMA {
+                b.2 = a.2;
+            }
+
+            let mut delta = b.2 - a.2;
+            match hue {
+                HueInterpolation::Shorter if delta > 180.0 => delta -= 360.0,
+                HueInterpolation::Shorter if delta < -180.0 => delta += 360.0,
+                HueInterpolation::Longer if delta > 0.0 && delta < 180.0 => delta -= 360.0,
+                HueInterpolation::Longer if delta > -180.0 && delta <= 0.0 => delta += 360.0,
+                _ => {}
+            }
+
+            let lch = (lerp(a.0, b.0, t), lerp(a.1, b.1, t), a.2 + delta * t);
+            oklab_to_rgb(oklch_to_oklab(lch))
+        }
+        InterpolationSpace::Cmyk => {
+            let (a, b) = (rgb_to_cmyk(a), rgb_to_cmyk(b));
+
+            cmyk_to_rgb((
+                lerp(a.0, b.0, t),
+                lerp(a.1, b.1, t),
+                lerp(a.2, b.2, t),
+                lerp(a.3, b.3, t),
+            ))
+        }
+    };
+
+    clamp_rgb(rgb)
+}
+
+// Stops must be sorted by position
+pub fn sample_gradient(
+    stops: &[GradientStop],
+    space: InterpolationSpace,
+    position: f32,
+) -> (f32, f32, f32) {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return (0.0, 0.0, 0.0),
+    };
+
+    if position <= first.position {
+        return first.rgb;
+    }
+
+    for pair in stops.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if position <= b.position {
+            let span = (b.position - a.position).max(f32::EPSILON);
+            return interpolate(a.rgb, b.rgb, (position - a.position) / span, space);
+        }
+    }
+
+    last.rgb
+}
+
+pub fn css_linear_gradient(stops: &[GradientStop], space: InterpolationSpace) -> String {
+    let color_stops: Vec<_> = match space.css_name() {
+        Some(_) => stops
+            .iter()
+            .map(|stop| format!("{} {:.0}%", rgb_to_str(stop.rgb), stop.position * 100.0))
+            .collect(),
+        None => (0..CSS_FALLBACK_STOPS)
+            .map(|i| {
+                let position = i as f32 / (CSS_FALLBACK_STOPS - 1) as f32;
+                let rgb = sample_gradient(stops, space, position);
+
+                format!("{} {:.1}%", rgb_to_str(rgb), position * 100.0)
+            })
+            .collect(),
+    };
+
+    format!(
+        "linear-gradient(in {}, {})",
+        space.css_name().unwrap_or("srgb"),
+        color_stops.join(", ")
+    )
+}
+
+// Vertices of a line strip through the cube following the gradient
+pub fn gradient_path(stops: &[GradientStop], space: InterpolationSpace) -> Vec<f32> {
+    if stops.len() < 2 {
+        return Vec::new();
+    }
+
+    point_vertices(
+        (0..PATH_SAMPLES)
+            .map(|i| sample_gradient(stops, space, i as f32 / (PATH_SAMPLES - 1) as f32)),
+    )
+}
+
+// Where a new stop goes so the others can stay put: an open end of the gradient, otherwise the
+// middle of the widest gap between stops
+pub fn new_stop_position(stops: &[GradientStop]) -> f32 {
+    let mut positions: Vec<_> = stops.iter().map(|stop| stop.position).collect();
+    positions.sort_by(f32::total_cmp);
+
+    let (first, last) = match (positions.first(), positions.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return 0.0,
+    };
+
+    let (mut widest, mut position) = (1.0 - last, 1.0);
+    if first > widest {
+        (widest, position) = (first, 0.0);
+    }
+    for pair in positions.windows(2) {
+        if pair[1] - pair[0] > widest {
+            (widest, position) = (pair[1] - pair[0], (pair[0] + pair[1]) / 2.0);
+        }
+    }
+
+    position
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub rgb: (f32, f32, f32),
+    pub onchange: Callback<Rc<Vec<f32>>>,
+}
+
+pub enum Msg {
+    AddStop,
+    RemoveStop(usize),
+    StopMoved(usize, f32),
+    SpaceChanged(InterpolationSpace),
+    // Result of the last draw, when it differs from the one shown
+    Drawn(Option<RenderError>),
+}
+
+pub struct GradientPanel {
+    stops: Vec<GradientStop>,
+    space: InterpolationSpace,
+    // Stops and space the preview was last drawn with
+    drawn: Option<(Vec<GradientStop>, InterpolationSpace)>,
+    preview: NodeRef,
+    draw_error: Option<RenderError>,
+}
+
+impl GradientPanel {
+    fn sorted_stops(&self) -> Vec<GradientStop> {
+        let mut stops = self.stops.clone();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+        stops
+    }
+}
+
+impl Component for GradientPanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            stops: Vec::new(),
+            space: InterpolationSpace::Oklab,
+            drawn: None,
+            preview: NodeRef::default(),
+            draw_error: None,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let on_space_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>();
+            let index = select.selected_index().max(0) as usize;
+
+            Msg::SpaceChanged(InterpolationSpace::ALL[index].0)
+        });
+
+        let spaces = InterpolationSpace::ALL.iter().map(|(space, name)| {
+            html! {
+                <option selected={*space == self.space}>{*name}</option>
+            }
+        });
+
+        let stops = self.stops.iter().enumerate().map(|(index, stop)| {
+            let background = format!(
+                "background-color: {}; width: 16px; height: 16px; display: inline-block;",
+                rgb_to_str(stop.rgb)
+            );
+            let on_position_change = ctx.link().callback(move |event: InputEvent| {
+                let input = event.target_unchecked_into::<HtmlInputElement>();
+
+                Msg::StopMoved(index, input.value_as_number() as f32)
+            });
+
+            html! {
+                <div>
+                    <span style={background}></span>
+                    <input
+                        type="range"
+                        min="0"
+                        max="1"
+                        step="0.01"
+                        value={stop.position.to_string()}
+                        oninput={on_position_change} />
+                    <span>{format!("{:.0}%", stop.position * 100.0)}</span>
+                    <button
+                        class="btn btn-sm btn-outline-secondary"
+                        onclick={ctx.link().callback(move |_| Msg::RemoveStop(index))}>
+                        {"×"}
+                    </button>
+                </div>
+            }
+        });
+
+        let css = if self.stops.len() >= 2 {
+            css_linear_gradient(&self.sorted_stops(), self.space)
+        } else {
+            String::new()
+        };
+
+        html! {
+            <div class="container">
+                <div class="row">
+                    <div class="col">
+                        <label>{"Gradient: "}</label>
+                        <select onchange={on_space_change}>
+                            { for spaces }
+                        </select>
+                        <button
+                            class="btn btn-sm btn-secondary"
+                            onclick={ctx.link().callback(|_| Msg::AddStop)}>
+                            {"Add current color"}
+                        </button>
+                    </div>
+                </div>
+                <div class="row">
+                    <div class="col">
+                        { for stops }
+                    </div>
+                    <div class="col">
+                        <canvas ref={self.preview.clone()} />
+                        <input class="form-control" type="text" readonly=true value={css} />
+                    </div>
+                </div>
+                { view_draw_error(&self.draw_error) }
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::AddStop => {
+                self.stops.push(GradientStop {
+                    rgb: ctx.props().rgb,
+                    position: new_stop_position(&self.stops),
+                });
+            }
+            Msg::RemoveStop(index) => {
+                self.stops.remove(index);
+            }
+            Msg::StopMoved(index, position) => {
+                self.stops[index].position = position.clamp(0.0, 1.0);
+            }
+            Msg::SpaceChanged(space) => {
+                self.space = space;
+            }
+            Msg::Drawn(error) => {
+                self.draw_error = error;
+
+                return true;
+            }
+        }
+
+        ctx.props()
+            .onchange
+            .emit(Rc::new(gradient_path(&self.sorted_stops(), self.space)));
+
+        true
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>) -> bool {
+        // Only adding a stop reads the color, and it does so when clicked
+        false
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let drawn = (self.sorted_stops(), self.space);
+        if self.drawn.as_ref() == Some(&drawn) {
+            return;
+        }
+        let stops = &drawn.0;
+
+        let mut row = Vec::with_capacity((PREVIEW_WIDTH * 4) as usize);
+        for x in 0..PREVIEW_WIDTH {
+            let position = x as f32 / (PREVIEW_WIDTH - 1) as f32;
+            push_rgba(&mut row, sample_gradient(stops, self.space, position));
+        }
+        let rgba = row.repeat(PREVIEW_HEIGHT as usize);
+
+        let error = put_rgba(&self.preview, PREVIEW_WIDTH, PREVIEW_HEIGHT, &rgba).err();
+        if error != self.draw_error {
+            ctx.link().send_message(Msg::Drawn(error));
+        }
+        self.drawn = Some(drawn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stop(rgb: (f32, f32, f32), position: f32) -> GradientStop {
+        GradientStop { rgb, position }
+    }
+
+    #[test]
+    fn css_gradients_use_native_interpolation() {
+        let stops = [stop((1.0, 0.0, 0.0), 0.0), stop((0.0, 0.0, 1.0), 1.0)];
+
+        assert_eq!(
+            css_linear_gradient(&stops, InterpolationSpace::Oklab),
+            "linear-gradient(in oklab, #ff0000 0%, #0000ff 100%)"
+        );
+        assert_eq!(
+            css_linear_gradient(&stops, InterpolationSpace::Oklch(HueInterpolation::Longer)),
+            "linear-gradient(in oklch longer hue, #ff0000 0%, #0000ff 100%)"
+        );
+    }
+
+    #[test]
+    fn css_gradients_sample_cmyk() {
+        let stops = [stop((1.0, 1.0, 1.0), 0.0), stop((0.0, 0.0, 0.0), 1.0)];
+        let css = css_linear_gradient(&stops, InterpolationSpace::Cmyk);
+
+        assert!(css.starts_with("linear-gradient(in srgb, #ffffff 0.0%, "));
+        assert!(css.ends_with(", #000000 100.0%)"));
+        assert_eq!(css.matches('#').count(), CSS_FALLBACK_STOPS);
+    }
+
+    #[test]
+    fn gray_takes_the_other_hue_in_oklch() {
+        let space = InterpolationSpace::Oklch(HueInterpolation::Shorter);
+        let (white, blue) = ((1.0, 1.0, 1.0), (0.0, 0.0, 1.0));
+        let hue = |rgb| oklab_to_oklch(rgb_to_oklab(rgb)).2;
+
+        // Clipping to sRGB shifts the hue a little, white's arbitrary hue would pull it far off
+        let middle = interpolate(white, blue, 0.5, space);
+        assert!((hue(middle) - hue(blue)).abs() < 5.0);
+    }
+
+    #[test]
+    fn new_stops_leave_the_others_in_place() {
+        assert_eq!(new_stop_position(&[]), 0.0);
+        assert_eq!(new_stop_position(&[stop((0.0, 0.0, 0.0), 0.0)]), 1.0);
+        assert_eq!(new_stop_position(&[stop((0.0, 0.0, 0.0), 0.7)]), 0.0);
+        assert_eq!(
+            new_stop_position(&[
+                stop((0.0, 0.0, 0.0), 1.0),
+                stop((0.0, 0.0, 0.0), 0.0),
+                stop((0.0, 0.0, 0.0), 0.2),
+            ]),
+            0.6
+        );
+    }
+}
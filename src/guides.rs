@@ -0,0 +1,134 @@
+use nalgebra_glm::{Mat4x4, Vec3, Vec4};
+
+use crate::cube::rgb_to_cube_position;
+use crate::solids::Solid;
+
+const EDGE_COLOR: (f32, f32, f32) = (0.8, 0.8, 0.8);
+// Pushes the lines just outside the faces so they don't z-fight with them
+const EDGE_SCALE: f32 = 1.002;
+// How far the arrows reach past the cube
+const AXIS_LENGTH: f32 = 1.3;
+const ARROW_HEAD: f32 = 0.08;
+const TICK_LENGTH: f32 = 0.04;
+
+fn push_line(vertices: &mut Vec<f32>, from: Vec3, to: Vec3, (r, g, b): (f32, f32, f32)) {
+    vertices.extend_from_slice(&[from.x, from.y, from.z, r, g, b]);
+    vertices.extend_from_slice(&[to.x, to.y, to.z, r, g, b]);
+}
+
+// Starts at the cube's origin corner and runs along one of its coordinates
+struct Axis {
+    direction: Vec3,
+    // The other two axes' directions
+    across: [Vec3; 2],
+    color: (f32, f32, f32),
+}
+
+// Red, green and blue for the RGB cube, cyan, magenta and yellow for the CMY one and so on
+fn axes(solid: Solid) -> [Axis; 3] {
+    let ends = [(1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)];
+    let directions =
+        ends.map(|end| rgb_to_cube_position(end) - rgb_to_cube_position((0.0, 0.0, 0.0)));
+
+    [0, 1, 2].map(|axis| Axis {
+        direction: directions[axis],
+        across: [directions[(axis + 1) % 3], directions[(axis + 2) % 3]],
+        color: solid.cube_color(ends[axis]),
+    })
+}
+
+fn corner_origin() -> Vec3 {
+    rgb_to_cube_position((0.0, 0.0, 0.0)) * EDGE_SCALE
+}
+
+// The twelve edges of a cube solid as line pairs, nothing for the round solids
+pub fn edge_vertices(solid: Solid) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    if !solid.is_cube() {
+        return vertices;
+    }
+
+    let corners = (0..8).map(|index| {
+        let bit = |b: u32| (index & b != 0) as u32 as f32;
+        (bit(1), bit(2), bit(4))
+    });
+    for from in corners.clone() {
+        for to in corners.clone() {
+            // Corners one coordinate apart, each edge once
+            let differing = [from.0 != to.0, from.1 != to.1, from.2 != to.2];
+            let ascending = from.0 + from.1 + from.2 < to.0 + to.1 + to.2;
+            if differing.iter().filter(|d| **d).count() == 1 && ascending {
+                push_line(
+                    &mut vertices,
+                    rgb_to_cube_position(from) * EDGE_SCALE,
+                    rgb_to_cube_position(to) * EDGE_SCALE,
+                    EDGE_COLOR,
+                );
+            }
+        }
+    }
+
+    vertices
+}
+
+// Arrows along a cube solid's coordinates from its origin corner as line pairs
+pub fn axis_vertices(solid: Solid) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    if !solid.is_cube() {
+        return vertices;
+    }
+
+    let origin = corner_origin();
+    for axis in axes(solid) {
+        let tip = origin + axis.direction * AXIS_LENGTH;
+        push_line(&mut vertices, origin, tip, axis.color);
+
+        let back = tip - axis.direction * ARROW_HEAD;
+        for across in axis.across {
+            for side in [across, -across] {
+                push_line(
+                    &mut vertices,
+                    tip,
+                    back + side * ARROW_HEAD / 2.0,
+                    axis.color,
+                );
+            }
+        }
+    }
+
+    vertices
+}
+
+// Marks every 10% along the axes, pointing away from the cube
+pub fn tick_vertices(solid: Solid) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    if !solid.is_cube() {
+        return vertices;
+    }
+
+    let origin = corner_origin();
+    for axis in axes(solid) {
+        for step in 1..10 {
+            let at = origin + axis.direction * (step as f32 / 10.0);
+
+            for across in axis.across {
+                push_line(&mut vertices, at, at - across * TICK_LENGTH, axis.color);
+            }
+        }
+    }
+
+    vertices
+}
+
+// Where a point in model space lands on a viewport of the given size, in pixels from the top
+// left, None when it's behind the camera
+pub fn project_to_screen(mvp: &Mat4x4, point: Vec3, width: f32, height: f32) -> Option<(f32, f32)> {
+    let clip = mvp * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc = clip.xyz() / clip.w;
+
+    Some(((ndc.x * 0.5 + 0.5) * width, (0.5 - ndc.y * 0.5) * height))
+}
@@ -0,0 +1,361 @@
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::canvas::{push_rgba, put_rgba, view_draw_error};
+use crate::color::{cmyk_to_rgb, rgb_to_cmyk};
+use crate::color_picker::CMYK;
+use crate::dot_gain::{apply_dot_gain, DotGainCurves};
+use crate::image::LoadedImage;
+use crate::renderer::RenderError;
+use crate::separation::ink_amount;
+
+// Resolution of the simulated output device, sets how many canvas pixels a screen cell spans
+const DOTS_PER_INCH: f32 = 600.0;
+const CANVAS_WIDTH: u32 = 400;
+const CANVAS_HEIGHT: u32 = 300;
+// Height to width ratio of elliptical dots
+const ELLIPSE_RATIO: f32 = 0.7;
+
+pub const SCREEN_ANGLES: [(CMYK, f32); 4] = [
+    (CMYK::Cyan, 15.0),
+    (CMYK::Magenta, 75.0),
+    (CMYK::Yellow, 0.0),
+    (CMYK::Key, 45.0),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DotShape {
+    Round,
+    Elliptical,
+    Square,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HalftoneSource {
+    Color,
+    Image,
+}
+
+// Whether a point of a screen cell, with x and y in -1..1, is covered by a dot of the given tone
+pub fn dot_covers(shape: DotShape, x: f32, y: f32, tone: f32) -> bool {
+    if tone <= 0.0 {
+        return false;
+    }
+    if tone >= 1.0 {
+        return true;
+    }
+
+    let ratio = match shape {
+        DotShape::Square => {
+            return x.abs().max(y.abs()).powi(2) < tone;
+        }
+        DotShape::Round => 1.0,
+        DotShape::Elliptical => ELLIPSE_RATIO,
+    };
+
+    // Dots grow from the cell center until they touch, then the paper shrinks to holes at the
+    // cell corners
+    if tone <= 0.5 {
+        let d = x * x + (y / ratio).powi(2);
+        PI * ratio * d / 4.0 < tone
+    } else {
+        let (cx, cy) = (1.0 - x.abs(), 1.0 - y.abs());
+        let d = cx * cx + (cy / ratio).powi(2);
+        PI * ratio * d / 4.0 >= 1.0 - tone
+    }
+}
+
+pub fn screen_coverage(shape: DotShape, lpi: f32, angle: f32, px: f32, py: f32, tone: f32) -> f32 {
+    let cell = DOTS_PER_INCH / lpi;
+    let (sin, cos) = angle.to_radians().sin_cos();
+
+    let u = (px * cos + py * sin) / cell;
+    let v = (-px * sin + py * cos) / cell;
+
+    let x = u.rem_euclid(1.0) * 2.0 - 1.0;
+    let y = v.rem_euclid(1.0) * 2.0 - 1.0;
+
+    if dot_covers(shape, x, y, tone) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+pub fn render_halftone(
+    width: u32,
+    height: u32,
+    shape: DotShape,
+    lpi: f32,
+    dot_gain: &DotGainCurves,
+    cmyk_at: impl Fn(u32, u32) -> (f32, f32, f32, f32),
+) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+    for py in 0..height {
+        for px in 0..width {
+            // Dots print larger than the file asks for
+            let cmyk = apply_dot_gain(cmyk_at(px, py), dot_gain);
+            let mut inked = [0.0; 4];
+            for (index, (plate, angle)) in SCREEN_ANGLES.iter().enumerate() {
+                inked[index] = screen_coverage(
+                    shape,
+                    lpi,
+                    *angle,
+                    px as f32,
+                    py as f32,
+                    ink_amount(cmyk, *plate),
+                );
+            }
+
+            push_rgba(
+                &mut rgba,
+                cmyk_to_rgb((inked[0], inked[1], inked[2], inked[3])),
+            );
+        }
+    }
+
+    rgba
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub cmyk: (f32, f32, f32, f32),
+    pub image: Option<Rc<LoadedImage>>,
+    pub dot_gain: DotGainCurves,
+}
+
+pub enum Msg {
+    Source(HalftoneSource),
+    Shape(DotShape),
+    Lpi(f32),
+    // Result of the last draw, when it differs from the one shown
+    Drawn(Option<RenderError>),
+}
+
+pub struct HalftonePanel {
+    source: HalftoneSource,
+    shape: DotShape,
+    lpi: f32,
+    // Props the halftone was last screened from
+    cmyk: (f32, f32, f32, f32),
+    image: Option<Rc<LoadedImage>>,
+    dot_gain: DotGainCurves,
+    // The screened preview and its size, None when the settings changed since
+    halftone: Option<(u32, u32, Vec<u8>)>,
+    canvas: NodeRef,
+    draw_error: Option<RenderError>,
+}
+
+impl HalftonePanel {
+    fn screen(&self) -> (u32, u32, Vec<u8>) {
+        match (&self.source, &self.image) {
+            (HalftoneSource::Image, Some(image)) => {
+                let scale = (CANVAS_WIDTH as f32 / image.width as f32)
+                    .min(CANVAS_HEIGHT as f32 / image.height as f32);
+                let (width, height) = (
+                    ((image.width as f32 * scale) as u32).max(1),
+                    ((image.height as f32 * scale) as u32).max(1),
+                );
+
+                let rgba = render_halftone(
+                    width,
+                    height,
+                    self.shape,
+                    self.lpi,
+                    &self.dot_gain,
+                    |px, py| {
+                        let sx = ((px as f32 / scale) as u32).min(image.width - 1);
+                        let sy = ((py as f32 / scale) as u32).min(image.height - 1);
+                        // Transparent pixels are bare paper
+                        image
+                            .pixel_color(sx, sy)
+                            .map_or((0.0, 0.0, 0.0, 0.0), rgb_to_cmyk)
+                    },
+                );
+
+                (width, height, rgba)
+            }
+            _ => {
+                let cmyk = self.cmyk;
+                let rgba = render_halftone(
+                    CANVAS_WIDTH,
+                    CANVAS_HEIGHT,
+                    self.shape,
+                    self.lpi,
+                    &self.dot_gain,
+                    |_, _| cmyk,
+                );
+
+                (CANVAS_WIDTH, CANVAS_HEIGHT, rgba)
+            }
+        }
+    }
+
+    // Whether the preview shows the image rather than the current color
+    fn shows_image(&self) -> bool {
+        self.source == HalftoneSource::Image && self.image.is_some()
+    }
+}
+
+impl Component for HalftonePanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            source: HalftoneSource::Color,
+            shape: DotShape::Round,
+            lpi: 60.0,
+            cmyk: ctx.props().cmyk,
+            image: ctx.props().image.clone(),
+            dot_gain: ctx.props().dot_gain.clone(),
+            halftone: None,
+            canvas: NodeRef::default(),
+            draw_error: None,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let on_source_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            match select.as_str() {
+                "image" => Msg::Source(HalftoneSource::Image),
+                _ => Msg::Source(HalftoneSource::Color),
+            }
+        });
+
+        let on_shape_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            match select.as_str() {
+                "elliptical" => Msg::Shape(DotShape::Elliptical),
+                "square" => Msg::Shape(DotShape::Square),
+                _ => Msg::Shape(DotShape::Round),
+            }
+        });
+
+        let on_lpi_change = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::Lpi(input.value_as_number() as f32)
+        });
+
+        html! {
+            <div class="container">
+                <div class="row">
+                    <div class="col">
+                        <label>{"Halftone: "}</label>
+                        <select onchange={on_source_change}>
+                            <option
+                                value="color"
+                                selected={self.source == HalftoneSource::Color}>
+                                {"Current color"}
+                            </option>
+                            <option
+                                value="image"
+                                selected={self.source == HalftoneSource::Image}
+                                disabled={ctx.props().image.is_none()}>
+                                {"Loaded image"}
+                            </option>
+                        </select>
+                        <select onchange={on_shape_change}>
+                            <option value="round" selected={self.shape == DotShape::Round}>
+                                {"Round"}
+                            </option>
+                            <option
+                                value="elliptical"
+                                selected={self.shape == DotShape::Elliptical}>
+                                {"Elliptical"}
+                            </option>
+                            <option value="square" selected={self.shape == DotShape::Square}>
+                                {"Square"}
+                            </option>
+                        </select>
+                    </div>
+                    <div class="col">
+                        <label>{"LPI: "}</label>
+                        <input
+                            type="range"
+                            min="10"
+                            max="200"
+                            step="5"
+                            value={self.lpi.to_string()}
+                            oninput={on_lpi_change} />
+                        <span>{self.lpi.to_string()}</span>
+                    </div>
+                </div>
+                <canvas ref={self.canvas.clone()} />
+                { view_draw_error(&self.draw_error) }
+            </div>
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Source(source) => {
+                self.source = source;
+            }
+            Msg::Shape(shape) => {
+                self.shape = shape;
+            }
+            Msg::Lpi(lpi) => {
+                self.lpi = lpi.max(1.0);
+            }
+            Msg::Drawn(error) => {
+                self.draw_error = error;
+
+                return true;
+            }
+        }
+        self.halftone = None;
+
+        true
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let props = ctx.props();
+        let same_image = match (&self.image, &props.image) {
+            (Some(current), Some(image)) => Rc::ptr_eq(current, image),
+            (None, None) => true,
+            _ => false,
+        };
+        let regained = props.dot_gain != self.dot_gain;
+        if same_image && self.cmyk == props.cmyk && !regained {
+            return false;
+        }
+
+        let was_image = self.shows_image();
+        let color_changed = self.cmyk != props.cmyk;
+        self.cmyk = props.cmyk;
+        self.image = props.image.clone();
+        self.dot_gain = props.dot_gain.clone();
+
+        // Only rescreen when what the preview shows changed
+        let stale = match (was_image, self.shows_image()) {
+            (true, true) => !same_image,
+            (false, false) => color_changed,
+            _ => true,
+        };
+        if stale || regained {
+            self.halftone = None;
+        }
+
+        true
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if self.halftone.is_some() {
+            return;
+        }
+
+        let (width, height, rgba) = self.halftone.insert(self.screen());
+        let drawn = put_rgba(&self.canvas, *width, *height, rgba).err();
+        if drawn != self.draw_error {
+            ctx.link().send_message(Msg::Drawn(drawn));
+        }
+    }
+}
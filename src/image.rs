@@ -0,0 +1,214 @@
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, HtmlInputElement, Url,
+};
+use yew::prelude::*;
+
+// Larger images are scaled down before their pixels are read back
+const MAX_IMAGE_SIZE: u32 = 512;
+
+#[derive(PartialEq, Eq)]
+pub struct LoadedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl LoadedImage {
+    // Every pixel's color in order, None where it's fully transparent. Those pixels aren't part of
+    // the picture, the color they store is whatever the encoder left there, usually black, so
+    // everything built from the image skips them or treats them as bare paper. Partly transparent
+    // pixels count as their color.
+    pub fn pixel_colors(&self) -> impl Iterator<Item = Option<(f32, f32, f32)>> + '_ {
+        self.pixels.chunks_exact(4).map(visible_color)
+    }
+
+    // Colors of the pixels that aren't fully transparent
+    pub fn rgb_pixels(&self) -> impl Iterator<Item = (f32, f32, f32)> + '_ {
+        self.pixel_colors().flatten()
+    }
+
+    pub fn pixel_color(&self, x: u32, y: u32) -> Option<(f32, f32, f32)> {
+        let index = ((y * self.width + x) * 4) as usize;
+
+        visible_color(&self.pixels[index..index + 4])
+    }
+}
+
+fn visible_color(pixel: &[u8]) -> Option<(f32, f32, f32)> {
+    (pixel[3] > 0).then(|| {
+        (
+            pixel[0] as f32 / 255.0,
+            pixel[1] as f32 / 255.0,
+            pixel[2] as f32 / 255.0,
+        )
+    })
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub onload: Callback<Rc<LoadedImage>>,
+}
+
+pub enum Msg {
+    FileSelected(Event),
+    ImageDecoded(HtmlImageElement, String),
+    DecodeFailed(String),
+}
+
+pub struct ImageLoader {
+    load_listener: Option<EventListener>,
+    error_listener: Option<EventListener>,
+    error: Option<String>,
+}
+
+impl ImageLoader {
+    // Done with the image either way, its listeners and object URL can go
+    fn finish_decoding(&mut self, url: &str) {
+        self.load_listener = None;
+        self.error_listener = None;
+        Url::revoke_object_url(url).ok();
+    }
+}
+
+impl Component for ImageLoader {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            load_listener: None,
+            error_listener: None,
+            error: None,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="form-group">
+                <label for="image_file">{"Image: "}</label>
+                <input
+                    id="image_file"
+                    type="file"
+                    accept="image/*"
+                    onchange={ctx.link().callback(Msg::FileSelected)} />
+                if let Some(error) = &self.error {
+                    <span class="text-danger">{error.clone()}</span>
+                }
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::FileSelected(event) => {
+                let input = event
+                    .target()
+                    .expect("No file input found.")
+                    .dyn_into::<HtmlInputElement>()
+                    .expect("Couldn't cast file input into HtmlInputElement");
+
+                let file = match input.files().and_then(|files| files.get(0)) {
+                    Some(file) => file,
+                    None => return false,
+                };
+
+                let url = Url::create_object_url_with_blob(&file)
+                    .expect("Couldn't create object url for image.");
+                let image = HtmlImageElement::new().expect("Couldn't create image element.");
+
+                let callback = ctx.link().callback({
+                    let image = image.clone();
+                    let url = url.clone();
+                    move |_: ()| Msg::ImageDecoded(image.clone(), url.clone())
+                });
+                self.load_listener = Some(EventListener::once(&image, "load", move |_| {
+                    callback.emit(())
+                }));
+
+                // Files the browser can't decode never load
+                let callback = ctx.link().callback({
+                    let url = url.clone();
+                    move |_: ()| Msg::DecodeFailed(url.clone())
+                });
+                self.error_listener = Some(EventListener::once(&image, "error", move |_| {
+                    callback.emit(())
+                }));
+
+                image.set_src(&url);
+
+                false
+            }
+            Msg::ImageDecoded(image, url) => {
+                self.finish_decoding(&url);
+
+                match read_pixels(&image) {
+                    Some(loaded) => {
+                        ctx.props().onload.emit(Rc::new(loaded));
+                        self.error = None;
+                    }
+                    None => {
+                        log::error!("Couldn't read pixels of loaded image.");
+                        self.error = Some("Couldn't read the image's pixels.".to_string());
+                    }
+                }
+
+                true
+            }
+            Msg::DecodeFailed(url) => {
+                self.finish_decoding(&url);
+                self.error = Some("Couldn't decode the file as an image.".to_string());
+
+                true
+            }
+        }
+    }
+}
+
+fn read_pixels(image: &HtmlImageElement) -> Option<LoadedImage> {
+    let (natural_width, natural_height) = (image.natural_width(), image.natural_height());
+    if natural_width == 0 || natural_height == 0 {
+        return None;
+    }
+
+    let scale = (MAX_IMAGE_SIZE as f64 / natural_width.max(natural_height) as f64).min(1.0);
+    let width = ((natural_width as f64 * scale) as u32).max(1);
+    let height = ((natural_height as f64 * scale) as u32).max(1);
+
+    let canvas = window()?
+        .document()?
+        .create_element("canvas")
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+
+    let context = canvas
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+    context
+        .draw_image_with_html_image_element_and_dw_and_dh(
+            image,
+            0.0,
+            0.0,
+            width as f64,
+            height as f64,
+        )
+        .ok()?;
+
+    let data = context
+        .get_image_data(0.0, 0.0, width as f64, height as f64)
+        .ok()?;
+
+    Some(LoadedImage {
+        width,
+        height,
+        pixels: data.data().0,
+    })
+}
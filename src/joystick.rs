@@ -0,0 +1,115 @@
+use web_sys::Element;
+use yew::prelude::*;
+
+const BASE_SIZE: f32 = 120.0;
+const KNOB_SIZE: f32 = 48.0;
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    // Stick position with x to the right and y forward, each in -1..1, (0, 0) when released
+    pub onmove: Callback<(f32, f32)>,
+}
+
+pub enum Msg {
+    Pressed(PointerEvent),
+    Moved(PointerEvent),
+    Released(PointerEvent),
+}
+
+pub struct Joystick {
+    pointer: Option<i32>,
+    position: (f32, f32),
+}
+
+impl Joystick {
+    fn move_to(&mut self, ctx: &Context<Self>, event: &PointerEvent) {
+        let radius = BASE_SIZE / 2.0;
+        let (x, y) = (
+            (event.offset_x() as f32 - radius) / radius,
+            (radius - event.offset_y() as f32) / radius,
+        );
+
+        // Keep the knob inside the base
+        let length = (x * x + y * y).sqrt().max(1.0);
+        self.position = (x / length, y / length);
+
+        ctx.props().onmove.emit(self.position);
+    }
+}
+
+impl Component for Joystick {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            pointer: None,
+            position: (0.0, 0.0),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let base = format!(
+            "position: fixed; left: 24px; bottom: 24px; width: {0}px; height: {0}px; \
+             border-radius: 50%; background-color: rgba(128, 128, 128, 0.3); \
+             touch-action: none; z-index: 10;",
+            BASE_SIZE
+        );
+
+        let travel = (BASE_SIZE - KNOB_SIZE) / 2.0;
+        let knob = format!(
+            "position: absolute; left: {}px; top: {}px; width: {2}px; height: {2}px; \
+             border-radius: 50%; background-color: rgba(255, 255, 255, 0.7); \
+             pointer-events: none;",
+            travel + self.position.0 * travel,
+            travel - self.position.1 * travel,
+            KNOB_SIZE
+        );
+
+        html! {
+            <div
+                style={base}
+                onpointerdown={ctx.link().callback(Msg::Pressed)}
+                onpointermove={ctx.link().callback(Msg::Moved)}
+                onpointerup={ctx.link().callback(Msg::Released)}
+                onpointercancel={ctx.link().callback(Msg::Released)}>
+                <div style={knob}></div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Pressed(event) => {
+                if self.pointer.is_some() {
+                    return false;
+                }
+
+                // Keep receiving moves when the finger slides off the base
+                let _ = event
+                    .target_unchecked_into::<Element>()
+                    .set_pointer_capture(event.pointer_id());
+                self.pointer = Some(event.pointer_id());
+                self.move_to(ctx, &event);
+            }
+            Msg::Moved(event) => {
+                if self.pointer != Some(event.pointer_id()) {
+                    return false;
+                }
+
+                self.move_to(ctx, &event);
+            }
+            Msg::Released(event) => {
+                if self.pointer != Some(event.pointer_id()) {
+                    return false;
+                }
+
+                self.pointer = None;
+                self.position = (0.0, 0.0);
+                ctx.props().onmove.emit(self.position);
+            }
+        }
+
+        true
+    }
+}
@@ -1,28 +1,339 @@
+mod animation;
+mod arcball;
 mod camera;
+mod canvas;
+mod color;
 mod color_picker;
 mod cube;
+mod dot_gain;
+mod export;
+mod gamuts;
+mod gradient;
+mod guides;
+mod halftone;
+mod image;
+mod joystick;
+mod named_colors;
+mod palette;
+mod point_cloud;
+mod rasterizer;
+mod renderer;
+mod resize;
+mod separation;
+mod solids;
+mod spot_library;
+mod viewpoints;
+mod voxels;
 
-use crate::color_picker::ColorPicker;
-use crate::cube::Cube;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::animation::{now, AnimationLoop};
+use crate::arcball::{normalized_point, Arcball};
+use crate::camera::{CameraMode, Projection};
+use crate::color_picker::{ColorPicker, PickedColor};
+use crate::cube::{Cube, SharedView, ViewState};
+use crate::dot_gain::{DotGainCurves, DotGainPanel};
+use crate::gradient::GradientPanel;
+use crate::halftone::HalftonePanel;
+use crate::image::{ImageLoader, LoadedImage};
+use crate::joystick::Joystick;
+use crate::named_colors::NamedColorPanel;
+use crate::palette::{PalettePanel, Swatch};
+use crate::point_cloud::{build_point_cloud, point_vertices, Sampling};
+use crate::separation::SeparationPanel;
+use crate::spot_library::SpotLibraryPanel;
+use crate::viewpoints::VIEWPOINTS;
 
 use gloo_events::EventListener;
 use nalgebra_glm::Vec3;
 use wasm_bindgen::JsCast;
-use web_sys::window;
+use web_sys::{
+    window, Element, HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement,
+};
 use yew::prelude::*;
 
+// Camera speed in units per second
+const MOVE_SPEED: f32 = 0.75;
+const SPRINT_MULTIPLIER: f32 = 3.0;
+// Longest frame step, so a backgrounded tab doesn't teleport the camera
+const MAX_FRAME_SECONDS: f32 = 0.1;
+// Camera turn per pixel of mouse movement at sensitivity 1
+const LOOK_DEGREES_PER_PIXEL: f32 = 0.2;
+// Zoom factor per pixel of wheel scrolling
+const ZOOM_PER_PIXEL: f32 = 1.002;
+const PIXELS_PER_LINE: f32 = 16.0;
+
 enum Msg {
     KeyDown(KeyboardEvent),
-    MouseMove(MouseEvent),
-    MouseDown(MouseEvent),
-    MouseUp(MouseEvent),
+    KeyUp(KeyboardEvent),
+    Blur,
+    Tick(f64),
+    PointerDown(PointerEvent),
+    PointerMove(PointerEvent),
+    PointerUp(PointerEvent),
+    JoystickMoved((f32, f32)),
+    Wheel(WheelEvent),
+    Resized(f32),
+    ImageLoaded(Rc<LoadedImage>),
+    SamplingChanged(Sampling),
+    PaletteExtracted(Rc<Vec<Swatch>>),
+    ColorChanged(PickedColor),
+    DotGainChanged(DotGainCurves),
+    GradientChanged(Rc<Vec<f32>>),
+    CameraModeChanged(CameraMode),
+    SensitivityChanged(f32),
+    ProjectionChanged(Projection),
+    ViewpointSelected(usize),
 }
 
 struct App {
-    camera: camera::Camera,
-    is_mouse_down: bool,
-    last_mouse_pos: (f32, f32),
-    cube_rotation: (f32, f32),
+    // Moved here and drawn by the cube's own frame loop, without rendering the app
+    view: SharedView,
+    pressed_keys: HashSet<String>,
+    last_frame: Option<f64>,
+    _animation: AnimationLoop,
+    camera_mode: CameraMode,
+    mouse_sensitivity: f32,
+    // Pointers pressed on the cube canvas and where they are
+    pointers: HashMap<i32, (f32, f32)>,
+    touch_device: bool,
+    joystick: (f32, f32),
+    image: Option<Rc<LoadedImage>>,
+    sampling: Sampling,
+    points: Rc<Vec<f32>>,
+    palette: Rc<Vec<f32>>,
+    color: PickedColor,
+    dot_gain: DotGainCurves,
+    gradient_path: Rc<Vec<f32>>,
+}
+
+fn pointer_position(event: &PointerEvent) -> (f32, f32) {
+    (event.offset_x() as f32, event.offset_y() as f32)
+}
+
+// Position on the canvas the event happened on, as seen by the arcball
+fn arcball_point(event: &PointerEvent, (x, y): (f32, f32)) -> (f32, f32) {
+    let canvas = event.target_unchecked_into::<Element>();
+
+    normalized_point(
+        x,
+        y,
+        canvas.client_width() as f32,
+        canvas.client_height() as f32,
+    )
+}
+
+impl App {
+    // Direction the held keys move the camera in, relative to where it looks
+    fn movement_direction(&self) -> Vec3 {
+        let mut direction = Vec3::new(self.joystick.0, 0.0, self.joystick.1);
+        for code in &self.pressed_keys {
+            direction += match code.as_str() {
+                "KeyW" => Vec3::new(0.0, 0.0, 1.0),
+                "KeyS" => Vec3::new(0.0, 0.0, -1.0),
+                "KeyA" => Vec3::new(-1.0, 0.0, 0.0),
+                "KeyD" => Vec3::new(1.0, 0.0, 0.0),
+                "KeyE" => Vec3::new(0.0, 1.0, 0.0),
+                "KeyQ" => Vec3::new(0.0, -1.0, 0.0),
+                _ => Vec3::zeros(),
+            };
+        }
+
+        direction
+    }
+
+    // Distance between and midpoint of the first two pointers
+    fn pinch(&self) -> Option<(f32, (f32, f32))> {
+        let mut pointers = self.pointers.values();
+        let (a, b) = (pointers.next()?, pointers.next()?);
+
+        let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        Some((distance, ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)))
+    }
+
+    // A drag of dx, dy pixels with one pointer
+    fn drag(&self, event: &PointerEvent, dx: f32, dy: f32) {
+        let (dx, dy) = (
+            dx * LOOK_DEGREES_PER_PIXEL * self.mouse_sensitivity,
+            dy * LOOK_DEGREES_PER_PIXEL * self.mouse_sensitivity,
+        );
+
+        let mut view = self.view.0.borrow_mut();
+        let ViewState { camera, arcball } = &mut *view;
+        match self.camera_mode {
+            CameraMode::OrbitModel => {
+                if arcball.is_dragging() {
+                    arcball.drag(
+                        arcball_point(event, pointer_position(event)),
+                        now(),
+                        &camera.view_rotation(),
+                        self.mouse_sensitivity,
+                    );
+                }
+            }
+            CameraMode::FreeLook => camera.rotate(dx, -dy),
+            CameraMode::OrbitCamera => camera.orbit(dx, -dy),
+        }
+    }
+
+    // Turns the cube towards one of the preset viewpoints
+    fn select_viewpoint(&self, index: usize) {
+        // Viewpoints are relative to the screen, so undo whatever the camera is doing
+        let orientation = VIEWPOINTS[index].orientation();
+        let mut view = self.view.0.borrow_mut();
+        let rotation = view.camera.view_rotation().inverse() * orientation;
+        view.arcball.animate_to(rotation);
+    }
+
+    fn is_sprinting(&self) -> bool {
+        self.pressed_keys.contains("ShiftLeft") || self.pressed_keys.contains("ShiftRight")
+    }
+
+    fn view_camera_controls(&self, ctx: &Context<Self>) -> Html {
+        let on_mode_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            match select.as_str() {
+                "free-look" => Msg::CameraModeChanged(CameraMode::FreeLook),
+                "orbit-camera" => Msg::CameraModeChanged(CameraMode::OrbitCamera),
+                _ => Msg::CameraModeChanged(CameraMode::OrbitModel),
+            }
+        });
+
+        let on_sensitivity_change = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::SensitivityChanged(input.value_as_number() as f32)
+        });
+
+        let on_projection_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            match select.as_str() {
+                "orthographic" => Msg::ProjectionChanged(Projection::Orthographic),
+                _ => Msg::ProjectionChanged(Projection::Perspective),
+            }
+        });
+
+        let mode = self.camera_mode;
+        let orthographic = self.view.0.borrow().camera.projection() == Projection::Orthographic;
+        html! {
+            <div class="row">
+                <div class="col">
+                    <label>{"Mouse: "}</label>
+                    <select onchange={on_mode_change}>
+                        <option value="orbit-model" selected={mode == CameraMode::OrbitModel}>
+                            {"Rotate cube"}
+                        </option>
+                        <option value="free-look" selected={mode == CameraMode::FreeLook}>
+                            {"Free look (click to lock pointer)"}
+                        </option>
+                        <option value="orbit-camera" selected={mode == CameraMode::OrbitCamera}>
+                            {"Orbit camera"}
+                        </option>
+                    </select>
+                    <select onchange={on_projection_change}>
+                        <option value="perspective" selected={!orthographic}>
+                            {"Perspective"}
+                        </option>
+                        <option value="orthographic" selected={orthographic}>
+                            {"Orthographic"}
+                        </option>
+                    </select>
+                </div>
+                <div class="col">
+                    <label>{"Sensitivity: "}</label>
+                    <input
+                        type="range"
+                        min="0.1"
+                        max="3"
+                        step="0.1"
+                        value={self.mouse_sensitivity.to_string()}
+                        oninput={on_sensitivity_change} />
+                    <span>{format!("{:.1}×", self.mouse_sensitivity)}</span>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_viewpoints(&self, ctx: &Context<Self>) -> Html {
+        let buttons = VIEWPOINTS.iter().enumerate().map(|(index, viewpoint)| {
+            html! {
+                <button
+                    class="btn btn-sm btn-outline-secondary"
+                    title={format!("Key {}", index + 1)}
+                    onclick={ctx.link().callback(move |_| Msg::ViewpointSelected(index))}>
+                    {viewpoint.name}
+                </button>
+            }
+        });
+
+        html! {
+            <div class="row">
+                <div class="col">
+                    <label>{"View: "}</label>
+                    { for buttons }
+                </div>
+            </div>
+        }
+    }
+
+    fn rebuild_point_cloud(&mut self) {
+        self.points = Rc::new(match &self.image {
+            Some(image) => build_point_cloud(image, self.sampling),
+            None => Vec::new(),
+        });
+    }
+
+    fn view_sampling(&self, ctx: &Context<Self>) -> Html {
+        let (mode, amount) = match self.sampling {
+            Sampling::Subsample(step) => ("subsample", step as f64),
+            Sampling::Binned(bins) => ("binned", bins as f64),
+        };
+
+        let on_mode_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            match select.as_str() {
+                "binned" => Msg::SamplingChanged(Sampling::Binned(32)),
+                _ => Msg::SamplingChanged(Sampling::Subsample(16)),
+            }
+        });
+
+        let binned = mode == "binned";
+        let on_amount_change = ctx.link().callback(move |event: InputEvent| {
+            let amount = event
+                .target_unchecked_into::<HtmlInputElement>()
+                .value_as_number()
+                .max(1.0);
+
+            if binned {
+                Msg::SamplingChanged(Sampling::Binned(amount as u32))
+            } else {
+                Msg::SamplingChanged(Sampling::Subsample(amount as usize))
+            }
+        });
+
+        html! {
+            <div class="row">
+                <div class="col">
+                    <label>{"Point cloud: "}</label>
+                    <select onchange={on_mode_change}>
+                        <option value="subsample" selected={!binned}>{"Every n-th pixel"}</option>
+                        <option value="binned" selected={binned}>{"Bins per channel"}</option>
+                    </select>
+                    <input
+                        type="number"
+                        min="1"
+                        max="256"
+                        value={amount.to_string()}
+                        oninput={on_amount_change} />
+                </div>
+            </div>
+        }
+    }
 }
 
 impl Component for App {
@@ -31,6 +342,8 @@ impl Component for App {
 
     fn create(ctx: &Context<Self>) -> Self {
         let keydown_callback = ctx.link().callback(Msg::KeyDown);
+        let keyup_callback = ctx.link().callback(Msg::KeyUp);
+        let blur_callback = ctx.link().callback(|_| Msg::Blur);
 
         EventListener::new(&window().unwrap(), "keydown", move |event| {
             let keyboard_event = event.clone().dyn_into::<KeyboardEvent>().unwrap();
@@ -39,31 +352,95 @@ impl Component for App {
         })
         .forget();
 
+        EventListener::new(&window().unwrap(), "keyup", move |event| {
+            let keyboard_event = event.clone().dyn_into::<KeyboardEvent>().unwrap();
+
+            keyup_callback.emit(keyboard_event)
+        })
+        .forget();
+
+        // Keys released while the window is unfocused never send keyup
+        EventListener::new(&window().unwrap(), "blur", move |event| {
+            blur_callback.emit(event.clone())
+        })
+        .forget();
+
         Self {
-            camera: camera::Camera::new(),
-            is_mouse_down: false,
-            last_mouse_pos: (0.0, 0.0),
-            cube_rotation: (0.0, 0.0),
+            view: SharedView(Rc::new(RefCell::new(ViewState {
+                camera: camera::Camera::new(),
+                arcball: Arcball::new(),
+            }))),
+            pressed_keys: HashSet::new(),
+            last_frame: None,
+            _animation: AnimationLoop::new(ctx.link().callback(Msg::Tick)),
+            camera_mode: CameraMode::OrbitModel,
+            mouse_sensitivity: 1.0,
+            pointers: HashMap::new(),
+            touch_device: window()
+                .map(|window| window.navigator().max_touch_points() > 0)
+                .unwrap_or(false),
+            joystick: (0.0, 0.0),
+            image: None,
+            sampling: Sampling::Subsample(16),
+            points: Rc::default(),
+            palette: Rc::default(),
+            color: PickedColor {
+                rgb: (1.0, 1.0, 1.0),
+                cmyk: (0.0, 0.0, 0.0, 0.0),
+            },
+            dot_gain: DotGainCurves::default(),
+            gradient_path: Rc::default(),
         }
     }
 
+    // html! expands component props into statements clippy takes for unnecessary operations
+    #[allow(clippy::unnecessary_operation)]
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let onmousemove_callback = ctx.link().callback(Msg::MouseMove);
-        let onmousedown_callback = ctx.link().callback(Msg::MouseDown);
-        let onmouseup_callback = ctx.link().callback(Msg::MouseUp);
-
         html! {
             <div class="container">
-                <ColorPicker />
+                <ColorPicker
+                    value={self.color}
+                    onchange={ctx.link().callback(Msg::ColorChanged)}
+                    dot_gain={self.dot_gain.clone()} />
+                <NamedColorPanel
+                    rgb={self.color.rgb}
+                    onpick={ctx.link().callback(Msg::ColorChanged)} />
+                <SpotLibraryPanel
+                    rgb={self.color.rgb}
+                    onpick={ctx.link().callback(Msg::ColorChanged)} />
+                <DotGainPanel
+                    cmyk={self.color.cmyk}
+                    curves={self.dot_gain.clone()}
+                    onchange={ctx.link().callback(Msg::DotGainChanged)} />
+                <GradientPanel
+                    rgb={self.color.rgb}
+                    onchange={ctx.link().callback(Msg::GradientChanged)} />
+                <ImageLoader onload={ctx.link().callback(Msg::ImageLoaded)} />
+                { self.view_sampling(ctx) }
+                <PalettePanel
+                    image={self.image.clone()}
+                    onextract={ctx.link().callback(Msg::PaletteExtracted)} />
+                <SeparationPanel image={self.image.clone()} dot_gain={self.dot_gain.clone()} />
+                <HalftonePanel
+                    cmyk={self.color.cmyk}
+                    image={self.image.clone()}
+                    dot_gain={self.dot_gain.clone()} />
+                { self.view_camera_controls(ctx) }
+                { self.view_viewpoints(ctx) }
                 <Cube
-                    view={self.camera.calculate_view_matrix()}
-                    projection={self.camera.calculate_projection_matrix()}
-                    onmousemove={onmousemove_callback}
-                    onmousedown={onmousedown_callback}
-                    onmouseup={onmouseup_callback}
-                    x_rotation={self.cube_rotation.0}
-                    y_rotation={self.cube_rotation.1}
+                    view={self.view.clone()}
+                    onpointerdown={ctx.link().callback(Msg::PointerDown)}
+                    onpointermove={ctx.link().callback(Msg::PointerMove)}
+                    onpointerup={ctx.link().callback(Msg::PointerUp)}
+                    onwheel={ctx.link().callback(Msg::Wheel)}
+                    onresize={ctx.link().callback(Msg::Resized)}
+                    points={self.points.clone()}
+                    palette={self.palette.clone()}
+                    gradient_path={self.gradient_path.clone()}
                 />
+                if self.touch_device {
+                    <Joystick onmove={ctx.link().callback(Msg::JoystickMoved)} />
+                }
             </div>
         }
     }
@@ -72,26 +449,217 @@ impl Component for App {
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Msg::KeyDown(key_event) => match key_event.key().as_str() {
-                "w" => self.camera.move_dir(Vec3::new(0.0, 0.0, 1.0)),
-                "s" => self.camera.move_dir(Vec3::new(0.0, 0.0, -1.0)),
-                "a" => self.camera.move_dir(Vec3::new(-1.0, 0.0, 0.0)),
-                "d" => self.camera.move_dir(Vec3::new(1.0, 0.0, 0.0)),
-                _ => return false,
-            },
-            Msg::MouseMove(event) => {
-                let (x, y) = (event.offset_x() as f32, event.offset_y() as f32);
-                //if self.is_mouse_down {
-                self.cube_rotation = (x - self.last_mouse_pos.0, y - self.last_mouse_pos.1);
-                // }
+            Msg::KeyDown(key_event) => {
+                // Ignore typing into the panels' inputs and menus
+                let typing = key_event.target().is_some_and(|target| {
+                    target.has_type::<HtmlInputElement>()
+                        || target.has_type::<HtmlSelectElement>()
+                        || target.has_type::<HtmlTextAreaElement>()
+                        || target
+                            .dyn_into::<HtmlElement>()
+                            .is_ok_and(|element| element.is_content_editable())
+                });
+                if typing {
+                    return false;
+                }
+
+                let code = key_event.code();
+                let viewpoint = code
+                    .strip_prefix("Digit")
+                    .and_then(|digit| digit.parse::<usize>().ok())
+                    .filter(|digit| (1..=VIEWPOINTS.len()).contains(digit));
+                if let Some(digit) = viewpoint {
+                    self.select_viewpoint(digit - 1);
+
+                    return false;
+                }
+
+                self.pressed_keys.insert(code);
+
+                return false;
+            }
+            Msg::KeyUp(key_event) => {
+                self.pressed_keys.remove(&key_event.code());
+
+                return false;
+            }
+            Msg::Blur => {
+                self.pressed_keys.clear();
+
+                return false;
+            }
+            Msg::Tick(now) => {
+                let elapsed = match self.last_frame.replace(now) {
+                    Some(last) => ((now - last) / 1000.0) as f32,
+                    None => 0.0,
+                };
+                let elapsed = elapsed.min(MAX_FRAME_SECONDS);
+
+                let mut view = self.view.0.borrow_mut();
+                view.arcball.step(elapsed);
+                let direction = self.movement_direction();
+                if direction == Vec3::zeros() {
+                    return false;
+                }
+                // Keys move at full speed, the joystick anywhere up to it
+                let throttle = direction.norm().min(1.0);
+
+                let speed = if self.is_sprinting() {
+                    MOVE_SPEED * SPRINT_MULTIPLIER
+                } else {
+                    MOVE_SPEED
+                };
+
+                view.camera.move_dir(direction, speed * throttle * elapsed);
+
+                return false;
+            }
+            Msg::PointerDown(event) => {
+                // The joystick shows up on the first touch
+                let first_touch = event.pointer_type() == "touch" && !self.touch_device;
+                if first_touch {
+                    self.touch_device = true;
+                }
+
+                // Keep receiving moves when the pointer leaves the canvas mid-drag
+                let canvas = event.target_unchecked_into::<Element>();
+                let _ = canvas.set_pointer_capture(event.pointer_id());
+
+                let position = pointer_position(&event);
+                self.pointers.insert(event.pointer_id(), position);
 
-                self.last_mouse_pos = (x, y);
+                let mut view = self.view.0.borrow_mut();
+                match (self.pointers.len(), self.camera_mode) {
+                    (1, CameraMode::OrbitModel) => view
+                        .arcball
+                        .begin_drag(arcball_point(&event, position), now()),
+                    (1, CameraMode::FreeLook) if event.pointer_type() == "mouse" => {
+                        canvas.request_pointer_lock()
+                    }
+                    // A second finger turns the drag into a pinch
+                    (2, _) => view.arcball.cancel_drag(),
+                    _ => {}
+                }
+
+                return first_touch;
+            }
+            Msg::PointerMove(event) => {
+                let locked = window()
+                    .and_then(|window| window.document())
+                    .and_then(|document| document.pointer_lock_element())
+                    .is_some();
+
+                // A locked pointer stays put, only its movement is reported
+                if locked && self.camera_mode == CameraMode::FreeLook {
+                    self.drag(&event, event.movement_x() as f32, event.movement_y() as f32);
+
+                    return false;
+                }
+
+                let before = self.pinch();
+                let position = pointer_position(&event);
+                let previous = match self.pointers.get_mut(&event.pointer_id()) {
+                    Some(previous) => std::mem::replace(previous, position),
+                    None => return false,
+                };
+
+                match (before, self.pinch()) {
+                    (Some((distance, middle)), Some((new_distance, new_middle))) => {
+                        let height = event.target_unchecked_into::<Element>().client_height();
+
+                        let camera = &mut self.view.0.borrow_mut().camera;
+                        camera.zoom(distance / new_distance.max(1.0));
+                        camera.pan(
+                            new_middle.0 - middle.0,
+                            new_middle.1 - middle.1,
+                            height as f32,
+                        );
+                    }
+                    _ => self.drag(&event, position.0 - previous.0, position.1 - previous.1),
+                }
+
+                return false;
+            }
+            Msg::PointerUp(event) => {
+                if self.pointers.remove(&event.pointer_id()).is_none() {
+                    return false;
+                }
+
+                let arcball = &mut self.view.0.borrow_mut().arcball;
+                match self.pointers.values().next() {
+                    // Lifting one finger of a pinch carries on rotating with the other
+                    Some(&position) if self.camera_mode == CameraMode::OrbitModel => {
+                        arcball.begin_drag(arcball_point(&event, position), now())
+                    }
+                    Some(_) => {}
+                    None => arcball.end_drag(now()),
+                }
+
+                return false;
+            }
+            Msg::JoystickMoved(position) => {
+                self.joystick = position;
+
+                return false;
+            }
+            Msg::Wheel(event) => {
+                let pixels = match event.delta_mode() {
+                    WheelEvent::DOM_DELTA_PIXEL => event.delta_y() as f32,
+                    _ => event.delta_y() as f32 * PIXELS_PER_LINE,
+                };
+
+                // Trackpad pinches arrive as wheel events with ctrl held
+                self.view
+                    .0
+                    .borrow_mut()
+                    .camera
+                    .zoom(ZOOM_PER_PIXEL.powf(pixels));
+
+                return false;
+            }
+            Msg::Resized(aspect) => {
+                self.view.0.borrow_mut().camera.set_aspect(aspect);
+
+                return false;
+            }
+            Msg::ImageLoaded(image) => {
+                self.image = Some(image);
+                self.palette = Rc::default();
+                self.rebuild_point_cloud();
+            }
+            Msg::SamplingChanged(sampling) => {
+                self.sampling = sampling;
+                self.rebuild_point_cloud();
+            }
+            Msg::PaletteExtracted(swatches) => {
+                self.palette = Rc::new(point_vertices(swatches.iter().map(|swatch| swatch.rgb)));
             }
-            Msg::MouseDown(_) => {
-                self.is_mouse_down = true;
+            Msg::ColorChanged(color) => {
+                self.color = color;
             }
-            Msg::MouseUp(_) => {
-                self.is_mouse_down = false;
+            Msg::DotGainChanged(curves) => {
+                self.dot_gain = curves;
+            }
+            Msg::GradientChanged(path) => {
+                self.gradient_path = path;
+            }
+            Msg::CameraModeChanged(mode) => {
+                self.camera_mode = mode;
+
+                if mode == CameraMode::OrbitCamera {
+                    self.view.0.borrow_mut().camera.look_at_target();
+                }
+            }
+            Msg::SensitivityChanged(sensitivity) => {
+                self.mouse_sensitivity = sensitivity.max(0.1);
+            }
+            Msg::ProjectionChanged(projection) => {
+                self.view.0.borrow_mut().camera.set_projection(projection);
+            }
+            Msg::ViewpointSelected(index) => {
+                self.select_viewpoint(index);
+
+                return false;
             }
         }
 
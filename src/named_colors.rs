@@ -0,0 +1,390 @@
+use std::cmp::Reverse;
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::color::{rgb_to_cmyk, rgb_to_oklab, rgb_to_str};
+use crate::color_picker::PickedColor;
+
+const SUGGESTION_COUNT: usize = 8;
+
+// https://www.w3.org/TR/css-color-4/#named-colors
+pub const CSS_NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+// X11 names missing from CSS, and the X11 meaning of names CSS redefined
+pub const X11_EXTENDED_COLORS: [(&str, u32); 8] = [
+    ("lightgoldenrod", 0xeedd82),
+    ("lightslateblue", 0x8470ff),
+    ("navyblue", 0x000080),
+    ("violetred", 0xd02090),
+    ("x11gray", 0xbebebe),
+    ("x11green", 0x00ff00),
+    ("x11maroon", 0xb03060),
+    ("x11purple", 0xa020f0),
+];
+
+pub fn hex_to_rgb(hex: u32) -> (f32, f32, f32) {
+    (
+        ((hex >> 16) & 0xff) as f32 / 255.0,
+        ((hex >> 8) & 0xff) as f32 / 255.0,
+        (hex & 0xff) as f32 / 255.0,
+    )
+}
+
+pub fn named_colors(extended: bool) -> impl Iterator<Item = &'static (&'static str, u32)> {
+    let extended: &[(&str, u32)] = if extended { &X11_EXTENDED_COLORS } else { &[] };
+
+    CSS_NAMED_COLORS.iter().chain(extended.iter())
+}
+
+// Euclidean distance in OKLab
+pub fn oklab_delta_e(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+    let (a, b) = (rgb_to_oklab(a), rgb_to_oklab(b));
+
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+pub fn nearest_named_color(rgb: (f32, f32, f32), extended: bool) -> (&'static str, f32) {
+    named_colors(extended)
+        .map(|(name, hex)| (*name, oklab_delta_e(rgb, hex_to_rgb(*hex))))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+// Subsequence match of the query in the name, rewarding consecutive and leading characters,
+// None if the query is not a subsequence
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let name = name.as_bytes();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for c in query.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let c = c.to_ascii_lowercase();
+        let found = name[position..].iter().position(|n| *n == c)? + position;
+
+        score += 1;
+        if found == 0 {
+            score += 3;
+        }
+        if previous_match.is_some_and(|previous| previous + 1 == found) {
+            score += 2;
+        }
+
+        previous_match = Some(found);
+        position = found + 1;
+    }
+
+    // Among equal matches prefer shorter names
+    Some(score * 100 - name.len() as i32)
+}
+
+pub fn search_named_colors(query: &str, extended: bool) -> Vec<&'static (&'static str, u32)> {
+    let mut matches: Vec<_> = named_colors(extended)
+        .filter_map(|entry| fuzzy_score(query, entry.0).map(|score| (score, entry)))
+        .collect();
+
+    matches.sort_by_key(|(score, _)| Reverse(*score));
+    matches
+        .into_iter()
+        .take(SUGGESTION_COUNT)
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+fn picked_color(hex: u32) -> PickedColor {
+    let rgb = hex_to_rgb(hex);
+
+    PickedColor {
+        rgb,
+        cmyk: rgb_to_cmyk(rgb),
+    }
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub rgb: (f32, f32, f32),
+    pub onpick: Callback<PickedColor>,
+}
+
+pub enum Msg {
+    QueryChanged(String),
+    ToggleExtended,
+    Picked(u32),
+    PickFirst,
+}
+
+pub struct NamedColorPanel {
+    query: String,
+    extended: bool,
+}
+
+impl Component for NamedColorPanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            query: String::new(),
+            extended: false,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let (nearest, distance) = nearest_named_color(ctx.props().rgb, self.extended);
+
+        let on_query_input = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::QueryChanged(input.value())
+        });
+
+        let on_query_keydown = ctx.link().batch_callback(|event: KeyboardEvent| {
+            if event.key() == "Enter" {
+                Some(Msg::PickFirst)
+            } else {
+                None
+            }
+        });
+
+        let suggestions = if self.query.trim().is_empty() {
+            Vec::new()
+        } else {
+            search_named_colors(&self.query, self.extended)
+        };
+        let suggestions = suggestions.into_iter().map(|(name, hex)| {
+            let hex = *hex;
+            let background = format!(
+                "background-color: {}; width: 16px; height: 16px; display: inline-block;",
+                rgb_to_str(hex_to_rgb(hex))
+            );
+
+            html! {
+                <li
+                    style="cursor: pointer;"
+                    onclick={ctx.link().callback(move |_| Msg::Picked(hex))}>
+                    <span style={background}></span>
+                    {format!(" {}", name)}
+                </li>
+            }
+        });
+
+        html! {
+            <div class="container">
+                <div class="row">
+                    <div class="col">
+                        <label>{"Nearest name: "}</label>
+                        <strong>{nearest}</strong>
+                        <small>{format!(" (ΔE OKLab {:.3})", distance)}</small>
+                    </div>
+                    <div class="col">
+                        <input
+                            type="search"
+                            placeholder="Color name"
+                            value={self.query.clone()}
+                            oninput={on_query_input}
+                            onkeydown={on_query_keydown} />
+                        <input
+                            type="checkbox"
+                            checked={self.extended}
+                            onchange={ctx.link().callback(|_| Msg::ToggleExtended)} />
+                        <label>{"X11 names"}</label>
+                        <ul class="list-unstyled">
+                            { for suggestions }
+                        </ul>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::QueryChanged(query) => {
+                self.query = query;
+            }
+            Msg::ToggleExtended => {
+                self.extended = !self.extended;
+            }
+            Msg::Picked(hex) => {
+                ctx.props().onpick.emit(picked_color(hex));
+                self.query.clear();
+            }
+            Msg::PickFirst => {
+                if let Some((_, hex)) = search_named_colors(&self.query, self.extended).first() {
+                    ctx.props().onpick.emit(picked_color(*hex));
+                    self.query.clear();
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_prefers_leading_and_consecutive_matches() {
+        let score = |name| fuzzy_score("red", name).unwrap();
+
+        // Leading over embedded, consecutive over scattered, shorter over longer
+        assert!(score("red") > score("indianred"));
+        assert!(score("indianred") > score("orangered"));
+        assert!(score("indianred") > score("mediumvioletred"));
+        assert_eq!(fuzzy_score("RED", "red"), fuzzy_score("r e d", "red"));
+        assert_eq!(fuzzy_score("xyz", "red"), None);
+        assert_eq!(search_named_colors("red", false)[0].0, "red");
+    }
+}
@@ -0,0 +1,520 @@
+use std::rc::Rc;
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::color::{clamp_rgb, oklab_to_rgb, rgb_to_oklab};
+use crate::image::LoadedImage;
+
+// Palettes are extracted from at most this many pixels
+const MAX_SAMPLES: usize = 65536;
+const OCTREE_DEPTH: usize = 6;
+const KMEANS_ITERATIONS: usize = 16;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaletteAlgorithm {
+    MedianCut,
+    Octree,
+    KMeans,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Swatch {
+    pub rgb: (f32, f32, f32),
+    // Fraction of the sampled pixels that belong to this swatch
+    pub share: f32,
+}
+
+pub fn extract_palette(
+    image: &LoadedImage,
+    algorithm: PaletteAlgorithm,
+    count: usize,
+) -> Vec<Swatch> {
+    let pixels = sample_pixels(image);
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    let mut swatches = match algorithm {
+        PaletteAlgorithm::MedianCut => median_cut(&pixels, count),
+        PaletteAlgorithm::Octree => octree(&pixels, count),
+        PaletteAlgorithm::KMeans => kmeans(&pixels, count),
+    };
+
+    swatches.sort_by(|a, b| b.share.total_cmp(&a.share));
+    swatches
+}
+
+fn sample_pixels(image: &LoadedImage) -> Vec<(f32, f32, f32)> {
+    let total = (image.width * image.height) as usize;
+    let step = (total / MAX_SAMPLES).max(1);
+
+    image.rgb_pixels().step_by(step).collect()
+}
+
+fn mean(pixels: &[(f32, f32, f32)]) -> (f32, f32, f32) {
+    let sum = pixels.iter().fold((0.0, 0.0, 0.0), |acc, p| {
+        (acc.0 + p.0, acc.1 + p.1, acc.2 + p.2)
+    });
+    let n = pixels.len().max(1) as f32;
+
+    (sum.0 / n, sum.1 / n, sum.2 / n)
+}
+
+fn channel(pixel: &(f32, f32, f32), index: usize) -> f32 {
+    match index {
+        0 => pixel.0,
+        1 => pixel.1,
+        _ => pixel.2,
+    }
+}
+
+fn widest_channel(pixels: &[(f32, f32, f32)]) -> (usize, f32) {
+    (0..3)
+        .map(|index| {
+            let (min, max) = pixels.iter().fold((1.0f32, 0.0f32), |(min, max), p| {
+                let v = channel(p, index);
+                (min.min(v), max.max(v))
+            });
+            (index, max - min)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+fn median_cut(pixels: &[(f32, f32, f32)], count: usize) -> Vec<Swatch> {
+    let mut boxes = vec![pixels.to_vec()];
+
+    while boxes.len() < count {
+        // Split the box with the widest channel range at its median
+        let (index, (channel_index, range)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (i, widest_channel(b)))
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+            .unwrap_or((0, (0, 0.0)));
+
+        if range <= 0.0 {
+            break;
+        }
+
+        let mut split = boxes.swap_remove(index);
+        split.sort_by(|a, b| channel(a, channel_index).total_cmp(&channel(b, channel_index)));
+        let upper = split.split_off(split.len() / 2);
+
+        boxes.push(split);
+        boxes.push(upper);
+    }
+
+    let total = pixels.len() as f32;
+    boxes
+        .iter()
+        .map(|b| Swatch {
+            rgb: mean(b),
+            share: b.len() as f32 / total,
+        })
+        .collect()
+}
+
+struct OctreeNode {
+    children: [Option<usize>; 8],
+    sum: (f32, f32, f32),
+    count: usize,
+    is_leaf: bool,
+}
+
+impl OctreeNode {
+    fn new(is_leaf: bool) -> Self {
+        Self {
+            children: [None; 8],
+            sum: (0.0, 0.0, 0.0),
+            count: 0,
+            is_leaf,
+        }
+    }
+}
+
+fn octree(pixels: &[(f32, f32, f32)], count: usize) -> Vec<Swatch> {
+    let mut nodes = vec![OctreeNode::new(false)];
+    // Inner nodes per level, candidates for merging their children
+    let mut levels: Vec<Vec<usize>> = vec![Vec::new(); OCTREE_DEPTH];
+    levels[0].push(0);
+    let mut leaf_count = 0;
+
+    for pixel in pixels {
+        let (r, g, b) = (
+            (pixel.0 * 255.0) as u8,
+            (pixel.1 * 255.0) as u8,
+            (pixel.2 * 255.0) as u8,
+        );
+
+        let mut node = 0;
+        for level in 0..OCTREE_DEPTH {
+            if nodes[node].is_leaf {
+                break;
+            }
+
+            let shift = 7 - level;
+            let child =
+                (((r >> shift) & 1) << 2 | ((g >> shift) & 1) << 1 | ((b >> shift) & 1)) as usize;
+
+            node = match nodes[node].children[child] {
+                Some(child) => child,
+                None => {
+                    let is_leaf = level + 1 == OCTREE_DEPTH;
+                    nodes.push(OctreeNode::new(is_leaf));
+                    let index = nodes.len() - 1;
+                    nodes[node].children[child] = Some(index);
+
+                    if is_leaf {
+                        leaf_count += 1;
+                    } else {
+                        levels[level + 1].push(index);
+                    }
+
+                    index
+                }
+            };
+        }
+
+        let node = &mut nodes[node];
+        node.sum = (
+            node.sum.0 + pixel.0,
+            node.sum.1 + pixel.1,
+            node.sum.2 + pixel.2,
+        );
+        node.count += 1;
+    }
+
+    // Merge the deepest inner nodes until the palette fits
+    while leaf_count > count {
+        let node = match levels.iter_mut().rev().find_map(|level| level.pop()) {
+            Some(node) => node,
+            None => break,
+        };
+
+        let mut merged = 0;
+        for child in nodes[node]
+            .children
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>()
+        {
+            let (sum, child_count) = (nodes[child].sum, nodes[child].count);
+            let parent = &mut nodes[node];
+            parent.sum = (
+                parent.sum.0 + sum.0,
+                parent.sum.1 + sum.1,
+                parent.sum.2 + sum.2,
+            );
+            parent.count += child_count;
+            merged += 1;
+        }
+
+        let parent = &mut nodes[node];
+        parent.children = [None; 8];
+        parent.is_leaf = true;
+        leaf_count = leaf_count + 1 - merged;
+    }
+
+    let total = pixels.len() as f32;
+    let mut swatches = Vec::new();
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        let node = &nodes[node];
+        if node.is_leaf {
+            if node.count > 0 {
+                let n = node.count as f32;
+                swatches.push(Swatch {
+                    rgb: (node.sum.0 / n, node.sum.1 / n, node.sum.2 / n),
+                    share: node.count as f32 / total,
+                });
+            }
+        } else {
+            stack.extend(node.children.iter().flatten());
+        }
+    }
+
+    swatches
+}
+
+fn oklab_distance_squared(a: &(f32, f32, f32), b: &(f32, f32, f32)) -> f32 {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)
+}
+
+fn kmeans(pixels: &[(f32, f32, f32)], count: usize) -> Vec<Swatch> {
+    let samples: Vec<_> = pixels.iter().copied().map(rgb_to_oklab).collect();
+
+    // Median cut gives deterministic, well spread initial centers
+    let mut centers: Vec<_> = median_cut(pixels, count)
+        .into_iter()
+        .map(|swatch| rgb_to_oklab(swatch.rgb))
+        .collect();
+    let mut assignments = vec![0; samples.len()];
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (sample, assignment) in samples.iter().zip(assignments.iter_mut()) {
+            let nearest = centers
+                .iter()
+                .enumerate()
+                .min_by(|a, b| {
+                    oklab_distance_squared(sample, a.1)
+                        .total_cmp(&oklab_distance_squared(sample, b.1))
+                })
+                .map(|(i, _)| i)
+                .unwrap();
+
+            if nearest != *assignment {
+                *assignment = nearest;
+                changed = true;
+            }
+        }
+
+        let mut sums = vec![((0.0, 0.0, 0.0), 0usize); centers.len()];
+        for (sample, assignment) in samples.iter().zip(assignments.iter()) {
+            let (sum, n) = &mut sums[*assignment];
+            *sum = (sum.0 + sample.0, sum.1 + sample.1, sum.2 + sample.2);
+            *n += 1;
+        }
+
+        for (center, (sum, n)) in centers.iter_mut().zip(sums.iter()) {
+            if *n > 0 {
+                let n = *n as f32;
+                *center = (sum.0 / n, sum.1 / n, sum.2 / n);
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let total = samples.len() as f32;
+    let mut counts = vec![0usize; centers.len()];
+    for assignment in assignments {
+        counts[assignment] += 1;
+    }
+
+    centers
+        .into_iter()
+        .zip(counts)
+        .filter(|(_, n)| *n > 0)
+        .map(|(center, n)| Swatch {
+            rgb: clamp_rgb(oklab_to_rgb(center)),
+            share: n as f32 / total,
+        })
+        .collect()
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub image: Option<Rc<LoadedImage>>,
+    pub onextract: Callback<Rc<Vec<Swatch>>>,
+}
+
+pub enum Msg {
+    AlgorithmChanged(PaletteAlgorithm),
+    CountChanged(usize),
+    Extract,
+}
+
+pub struct PalettePanel {
+    algorithm: PaletteAlgorithm,
+    count: usize,
+    image: Option<Rc<LoadedImage>>,
+    swatches: Rc<Vec<Swatch>>,
+}
+
+impl Component for PalettePanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            algorithm: PaletteAlgorithm::KMeans,
+            count: 8,
+            image: ctx.props().image.clone(),
+            swatches: Rc::default(),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let same_image = match (&self.image, &ctx.props().image) {
+            (Some(old), Some(new)) => Rc::ptr_eq(old, new),
+            (None, None) => true,
+            _ => false,
+        };
+
+        // Swatches of the previous image no longer apply
+        if !same_image {
+            self.image = ctx.props().image.clone();
+            self.swatches = Rc::default();
+        }
+
+        !same_image
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let on_algorithm_change = ctx.link().callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            match select.as_str() {
+                "median_cut" => Msg::AlgorithmChanged(PaletteAlgorithm::MedianCut),
+                "octree" => Msg::AlgorithmChanged(PaletteAlgorithm::Octree),
+                _ => Msg::AlgorithmChanged(PaletteAlgorithm::KMeans),
+            }
+        });
+
+        let on_count_change = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::CountChanged(input.value_as_number().clamp(1.0, 64.0) as usize)
+        });
+
+        let swatches = self.swatches.iter().map(|swatch| {
+            let (r, g, b) = swatch.rgb;
+            let background = format!(
+                "background-color: rgb({}, {}, {}); width: 48px; height: 48px;",
+                (r * 255.0) as u8,
+                (g * 255.0) as u8,
+                (b * 255.0) as u8
+            );
+
+            html! {
+                <div class="col-auto text-center">
+                    <div style={background}></div>
+                    <small>{format!("{:.1}%", swatch.share * 100.0)}</small>
+                </div>
+            }
+        });
+
+        html! {
+            <div class="container">
+                <div class="row">
+                    <div class="col">
+                        <label>{"Palette: "}</label>
+                        <select onchange={on_algorithm_change}>
+                            <option
+                                value="median_cut"
+                                selected={self.algorithm == PaletteAlgorithm::MedianCut}>
+                                {"Median cut"}
+                            </option>
+                            <option
+                                value="octree"
+                                selected={self.algorithm == PaletteAlgorithm::Octree}>
+                                {"Octree"}
+                            </option>
+                            <option
+                                value="kmeans"
+                                selected={self.algorithm == PaletteAlgorithm::KMeans}>
+                                {"k-means (OKLab)"}
+                            </option>
+                        </select>
+                        <input
+                            type="number"
+                            min="1"
+                            max="64"
+                            value={self.count.to_string()}
+                            oninput={on_count_change} />
+                        <button
+                            class="btn btn-sm btn-secondary"
+                            disabled={ctx.props().image.is_none()}
+                            onclick={ctx.link().callback(|_| Msg::Extract)}>
+                            {"Extract"}
+                        </button>
+                    </div>
+                </div>
+                <div class="row">
+                    { for swatches }
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::AlgorithmChanged(algorithm) => {
+                self.algorithm = algorithm;
+            }
+            Msg::CountChanged(count) => {
+                self.count = count;
+            }
+            Msg::Extract => {
+                let image = match &ctx.props().image {
+                    Some(image) => image,
+                    None => return false,
+                };
+
+                self.swatches = Rc::new(extract_palette(image, self.algorithm, self.count));
+                ctx.props().onextract.emit(self.swatches.clone());
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Left three quarters red, the rest blue
+    fn two_color_image() -> LoadedImage {
+        let (width, height) = (8, 4);
+        let pixels = (0..width * height)
+            .flat_map(|index| {
+                if index % width < 6 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 255, 255]
+                }
+            })
+            .collect();
+
+        LoadedImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn shares_sum_to_one() {
+        let image = two_color_image();
+
+        for algorithm in [
+            PaletteAlgorithm::MedianCut,
+            PaletteAlgorithm::Octree,
+            PaletteAlgorithm::KMeans,
+        ] {
+            for count in 1..=4 {
+                let total: f32 = extract_palette(&image, algorithm, count)
+                    .iter()
+                    .map(|swatch| swatch.share)
+                    .sum();
+                assert!((total - 1.0).abs() < 1e-4, "shares sum to {}", total);
+            }
+        }
+    }
+
+    #[test]
+    fn kmeans_finds_both_colors() {
+        let swatches = extract_palette(&two_color_image(), PaletteAlgorithm::KMeans, 2);
+
+        assert_eq!(swatches.len(), 2);
+        for (swatch, (rgb, share)) in swatches
+            .iter()
+            .zip([((1.0, 0.0, 0.0), 0.75), ((0.0, 0.0, 1.0), 0.25)])
+        {
+            let error = (swatch.rgb.0 - rgb.0)
+                .abs()
+                .max((swatch.rgb.1 - rgb.1).abs())
+                .max((swatch.rgb.2 - rgb.2).abs());
+            assert!(error < 1e-3, "expected {:?}, got {:?}", rgb, swatch.rgb);
+            assert!((swatch.share - share).abs() < 1e-4);
+        }
+    }
+}
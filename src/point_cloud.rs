@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+
+use crate::cube::rgb_to_cube_position;
+use crate::image::LoadedImage;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Sampling {
+    // Every n-th pixel becomes a point
+    Subsample(usize),
+    // One point per occupied bin of an n×n×n grid over the cube
+    Binned(u32),
+}
+
+// Interleaved position and color, same layout as the cube vertices
+pub fn build_point_cloud(image: &LoadedImage, sampling: Sampling) -> Vec<f32> {
+    let mut vertices = Vec::new();
+
+    match sampling {
+        Sampling::Subsample(step) => {
+            for rgb in image.rgb_pixels().step_by(step.max(1)) {
+                push_point(&mut vertices, rgb);
+            }
+        }
+        Sampling::Binned(bins) => {
+            let bins = bins.max(1);
+            let quantize = |v: f32| ((v * bins as f32) as u32).min(bins - 1);
+
+            let mut occupied = HashSet::new();
+            for (r, g, b) in image.rgb_pixels() {
+                occupied.insert((quantize(r), quantize(g), quantize(b)));
+            }
+
+            let center = |bin: u32| (bin as f32 + 0.5) / bins as f32;
+            for (r, g, b) in occupied {
+                push_point(&mut vertices, (center(r), center(g), center(b)));
+            }
+        }
+    }
+
+    vertices
+}
+
+pub fn point_vertices(colors: impl Iterator<Item = (f32, f32, f32)>) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    for rgb in colors {
+        push_point(&mut vertices, rgb);
+    }
+
+    vertices
+}
+
+fn push_point(vertices: &mut Vec<f32>, rgb: (f32, f32, f32)) {
+    let position = rgb_to_cube_position(rgb);
+
+    vertices.extend_from_slice(&[position.x, position.y, position.z, rgb.0, rgb.1, rgb.2]);
+}
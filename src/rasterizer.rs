@@ -0,0 +1,520 @@
+use nalgebra_glm::{Vec3, Vec4};
+
+use crate::renderer::VertexLayout;
+
+// Fixed function state of a draw, matching the WebGL defaults used by the scenes
+#[derive(Clone, Copy)]
+pub struct DrawState {
+    pub depth_test: bool,
+    pub depth_write: bool,
+    // Blends color with SRC_ALPHA, ONE_MINUS_SRC_ALPHA and alpha with ONE, ONE_MINUS_SRC_ALPHA
+    pub blend: bool,
+}
+
+impl Default for DrawState {
+    fn default() -> Self {
+        Self {
+            depth_test: true,
+            depth_write: true,
+            blend: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ClipVertex {
+    position: Vec4,
+    varying: Vec3,
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position + (other.position - self.position) * t,
+            varying: self.varying + (other.varying - self.varying) * t,
+        }
+    }
+}
+
+// Vertex after the perspective divide, in pixels with y down
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    depth: f32,
+    inv_w: f32,
+    varying: Vec3,
+}
+
+// Signed distances to the planes of -w <= x, y, z <= w in clip space, inside is positive
+const CLIP_PLANES: [fn(&Vec4) -> f32; 6] = [
+    |p| p.w + p.x,
+    |p| p.w - p.x,
+    |p| p.w + p.y,
+    |p| p.w - p.y,
+    |p| p.w + p.z,
+    |p| p.w - p.z,
+];
+
+// Clips a polygon in clip space to -w <= x, y, z <= w, Sutherland-Hodgman style
+fn clip_polygon(mut polygon: Vec<ClipVertex>) -> Vec<ClipVertex> {
+    for distance in CLIP_PLANES {
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+
+        for (index, current) in polygon.iter().enumerate() {
+            let previous = &polygon[(index + polygon.len() - 1) % polygon.len()];
+            let (d_current, d_previous) =
+                (distance(&current.position), distance(&previous.position));
+
+            if d_current >= 0.0 {
+                if d_previous < 0.0 {
+                    clipped.push(previous.lerp(current, d_previous / (d_previous - d_current)));
+                }
+                clipped.push(*current);
+            } else if d_previous >= 0.0 {
+                clipped.push(previous.lerp(current, d_previous / (d_previous - d_current)));
+            }
+        }
+
+        polygon = clipped;
+        if polygon.len() < 3 {
+            return Vec::new();
+        }
+    }
+
+    polygon
+}
+
+// Clips a line segment in clip space to -w <= x, y, z <= w, None when it's all outside
+fn clip_segment(mut a: ClipVertex, mut b: ClipVertex) -> Option<[ClipVertex; 2]> {
+    for distance in CLIP_PLANES {
+        let (d_a, d_b) = (distance(&a.position), distance(&b.position));
+        match (d_a >= 0.0, d_b >= 0.0) {
+            (true, true) => {}
+            (false, false) => return None,
+            (true, false) => b = a.lerp(&b, d_a / (d_a - d_b)),
+            (false, true) => a = a.lerp(&b, d_a / (d_a - d_b)),
+        }
+    }
+
+    Some([a, b])
+}
+
+// CPU stand-in for a WebGL render target, renders triangles into an RGBA buffer
+pub struct SoftwareTarget {
+    width: u32,
+    height: u32,
+    color: Vec<[f32; 4]>,
+    depth: Vec<f32>,
+}
+
+impl SoftwareTarget {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+
+        Self {
+            width,
+            height,
+            color: vec![[0.0; 4]; size],
+            depth: vec![1.0; size],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+
+    pub fn begin_frame(&mut self, (r, g, b, a): (f32, f32, f32, f32)) {
+        self.color.fill([r, g, b, a]);
+        self.depth.fill(1.0);
+    }
+
+    // Rows from the top, four bytes per pixel
+    pub fn pixels(&self) -> Vec<u8> {
+        self.color
+            .iter()
+            .flat_map(|color| color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+            .collect()
+    }
+
+    // Runs indexed triangles through the pipeline. The vertex shader returns the clip space
+    // position and a varying, which reaches the fragment shader perspective-correctly interpolated.
+    pub fn draw_triangles(
+        &mut self,
+        vertices: &[f32],
+        layout: &VertexLayout,
+        indices: &[u32],
+        state: DrawState,
+        vertex_shader: impl Fn(&[f32]) -> (Vec4, Vec3),
+        fragment_shader: impl Fn(Vec3) -> [f32; 4],
+    ) {
+        let stride = layout.floats_per_vertex();
+        let shaded: Vec<_> = vertices
+            .chunks_exact(stride)
+            .map(|vertex| {
+                let (position, varying) = vertex_shader(vertex);
+                ClipVertex { position, varying }
+            })
+            .collect();
+
+        for triangle in indices.chunks_exact(3) {
+            let polygon = clip_polygon(triangle.iter().map(|&i| shaded[i as usize]).collect());
+            let screen: Vec<_> = polygon.iter().map(|v| self.to_screen(v)).collect();
+
+            for i in 1..screen.len().saturating_sub(1) {
+                self.fill_triangle(
+                    [&screen[0], &screen[i], &screen[i + 1]],
+                    state,
+                    &fragment_shader,
+                );
+            }
+        }
+    }
+
+    // Draws one pixel wide lines between vertex pairs like GL LINES, or between each vertex and the
+    // next like LINE_STRIP when strip is set
+    pub fn draw_lines(
+        &mut self,
+        vertices: &[f32],
+        layout: &VertexLayout,
+        strip: bool,
+        state: DrawState,
+        vertex_shader: impl Fn(&[f32]) -> (Vec4, Vec3),
+        fragment_shader: impl Fn(Vec3) -> [f32; 4],
+    ) {
+        let shaded: Vec<_> = vertices
+            .chunks_exact(layout.floats_per_vertex())
+            .map(|vertex| {
+                let (position, varying) = vertex_shader(vertex);
+                ClipVertex { position, varying }
+            })
+            .collect();
+
+        let step = if strip { 1 } else { 2 };
+        for index in (0..shaded.len().saturating_sub(1)).step_by(step) {
+            let segment = match clip_segment(shaded[index], shaded[index + 1]) {
+                Some(segment) => segment,
+                None => continue,
+            };
+            let (a, b) = (self.to_screen(&segment[0]), self.to_screen(&segment[1]));
+
+            // One fragment per pixel along the longer direction
+            let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as u32;
+            for i in 0..=steps {
+                let t = i as f32 / steps as f32;
+                let (x, y) = (a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+                if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+                    continue;
+                }
+
+                let inv_w = a.inv_w + (b.inv_w - a.inv_w) * t;
+                let varying = (a.varying * a.inv_w * (1.0 - t) + b.varying * b.inv_w * t) / inv_w;
+                let depth = a.depth + (b.depth - a.depth) * t;
+
+                self.write_fragment(x as u32, y as u32, depth, varying, state, &fragment_shader);
+            }
+        }
+    }
+
+    // Draws each vertex as a size × size pixel square, or a disc when round, like gl_PointSize
+    pub fn draw_points(
+        &mut self,
+        vertices: &[f32],
+        layout: &VertexLayout,
+        size: f32,
+        round: bool,
+        vertex_shader: impl Fn(&[f32]) -> (Vec4, Vec3),
+        fragment_shader: impl Fn(Vec3) -> [f32; 4],
+    ) {
+        let radius = size / 2.0;
+
+        for vertex in vertices.chunks_exact(layout.floats_per_vertex()) {
+            let (position, varying) = vertex_shader(vertex);
+            let inside = [position.x, position.y, position.z]
+                .iter()
+                .all(|c| c.abs() <= position.w);
+            if !inside {
+                continue;
+            }
+
+            let center = self.to_screen(&ClipVertex { position, varying });
+            let min_x = (center.x - radius).round().max(0.0) as u32;
+            let max_x = ((center.x + radius).round().max(0.0) as u32).min(self.width);
+            let min_y = (center.y - radius).round().max(0.0) as u32;
+            let max_y = ((center.y + radius).round().max(0.0) as u32).min(self.height);
+
+            for py in min_y..max_y {
+                for px in min_x..max_x {
+                    let (dx, dy) = (px as f32 + 0.5 - center.x, py as f32 + 0.5 - center.y);
+                    if round && dx * dx + dy * dy > radius * radius {
+                        continue;
+                    }
+
+                    self.write_fragment(
+                        px,
+                        py,
+                        center.depth,
+                        varying,
+                        DrawState::default(),
+                        &fragment_shader,
+                    );
+                }
+            }
+        }
+    }
+
+    fn to_screen(&self, vertex: &ClipVertex) -> ScreenVertex {
+        let inv_w = 1.0 / vertex.position.w;
+        let ndc = vertex.position.xyz() * inv_w;
+
+        ScreenVertex {
+            x: (ndc.x * 0.5 + 0.5) * self.width as f32,
+            y: (0.5 - ndc.y * 0.5) * self.height as f32,
+            depth: ndc.z * 0.5 + 0.5,
+            inv_w,
+            varying: vertex.varying,
+        }
+    }
+
+    fn fill_triangle(
+        &mut self,
+        [a, b, c]: [&ScreenVertex; 3],
+        state: DrawState,
+        fragment_shader: &impl Fn(Vec3) -> [f32; 4],
+    ) {
+        let edge = |p: &ScreenVertex, q: &ScreenVertex, x: f32, y: f32| {
+            (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x)
+        };
+
+        let area = edge(a, b, c.x, c.y);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+        let max_x = (a.x.max(b.x).max(c.x).ceil() as u32).min(self.width);
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+        let max_y = (a.y.max(b.y).max(c.y).ceil() as u32).min(self.height);
+
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+
+                // Barycentric weights, all positive inside whatever the winding
+                let weights = [
+                    edge(b, c, x, y) / area,
+                    edge(c, a, x, y) / area,
+                    edge(a, b, x, y) / area,
+                ];
+                if weights.iter().any(|weight| *weight < 0.0) {
+                    continue;
+                }
+
+                let depth = weights[0] * a.depth + weights[1] * b.depth + weights[2] * c.depth;
+                let inv_w = weights[0] * a.inv_w + weights[1] * b.inv_w + weights[2] * c.inv_w;
+                let varying = (a.varying * weights[0] * a.inv_w
+                    + b.varying * weights[1] * b.inv_w
+                    + c.varying * weights[2] * c.inv_w)
+                    / inv_w;
+
+                self.write_fragment(px, py, depth, varying, state, fragment_shader);
+            }
+        }
+    }
+
+    // Depth tests, shades and blends one pixel
+    fn write_fragment(
+        &mut self,
+        x: u32,
+        y: u32,
+        depth: f32,
+        varying: Vec3,
+        state: DrawState,
+        fragment_shader: &impl Fn(Vec3) -> [f32; 4],
+    ) {
+        let index = (y * self.width + x) as usize;
+        if state.depth_test && depth >= self.depth[index] {
+            return;
+        }
+
+        let source = fragment_shader(varying);
+        let destination = &mut self.color[index];
+        *destination = if state.blend {
+            let alpha = source[3];
+            let blended = [0, 1, 2].map(|i| source[i] * alpha + destination[i] * (1.0 - alpha));
+            [
+                blended[0],
+                blended[1],
+                blended[2],
+                alpha + destination[3] * (1.0 - alpha),
+            ]
+        } else {
+            source
+        };
+
+        if state.depth_write {
+            self.depth[index] = depth;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass_through(vertex: &[f32]) -> (Vec4, Vec3) {
+        (
+            Vec4::new(vertex[0], vertex[1], vertex[2], 1.0),
+            Vec3::new(vertex[3], vertex[4], vertex[5]),
+        )
+    }
+
+    fn opaque(color: Vec3) -> [f32; 4] {
+        [color.x, color.y, color.z, 1.0]
+    }
+
+    fn pixel(target: &SoftwareTarget, x: u32, y: u32) -> [u8; 4] {
+        let pixels = target.pixels();
+        let index = ((y * target.width() + x) * 4) as usize;
+
+        [
+            pixels[index],
+            pixels[index + 1],
+            pixels[index + 2],
+            pixels[index + 3],
+        ]
+    }
+
+    #[test]
+    fn interpolates_color_across_a_triangle() {
+        let mut target = SoftwareTarget::new(8, 8);
+        target.begin_frame((0.0, 0.0, 0.0, 1.0));
+
+        // A quad covering the whole target, red on the left and blue on the right
+        let vertices = [
+            -1.0, -1.0, 0.0, 1.0, 0.0, 0.0, //
+            1.0, -1.0, 0.0, 0.0, 0.0, 1.0, //
+            1.0, 1.0, 0.0, 0.0, 0.0, 1.0, //
+            -1.0, 1.0, 0.0, 1.0, 0.0, 0.0, //
+        ];
+        target.draw_triangles(
+            &vertices,
+            &VertexLayout::position_color(),
+            &[0, 1, 2, 2, 3, 0],
+            DrawState::default(),
+            pass_through,
+            opaque,
+        );
+
+        assert_eq!(pixel(&target, 0, 4), [239, 0, 16, 255]);
+        assert_eq!(pixel(&target, 7, 4), [16, 0, 239, 255]);
+    }
+
+    #[test]
+    fn nearer_triangle_wins_in_either_order() {
+        let near = [
+            -1.0, -1.0, -0.5, 0.0, 1.0, 0.0, //
+            3.0, -1.0, -0.5, 0.0, 1.0, 0.0, //
+            -1.0, 3.0, -0.5, 0.0, 1.0, 0.0, //
+        ];
+        let far = [
+            -1.0, -1.0, 0.5, 1.0, 0.0, 0.0, //
+            3.0, -1.0, 0.5, 1.0, 0.0, 0.0, //
+            -1.0, 3.0, 0.5, 1.0, 0.0, 0.0, //
+        ];
+
+        for order in [[&near, &far], [&far, &near]] {
+            let mut target = SoftwareTarget::new(4, 4);
+            target.begin_frame((0.0, 0.0, 0.0, 1.0));
+            for vertices in order {
+                target.draw_triangles(
+                    vertices,
+                    &VertexLayout::position_color(),
+                    &[0, 1, 2],
+                    DrawState::default(),
+                    pass_through,
+                    opaque,
+                );
+            }
+
+            assert_eq!(pixel(&target, 2, 2), [0, 255, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn lines_are_clipped_and_depth_tested() {
+        let mut target = SoftwareTarget::new(8, 8);
+        target.begin_frame((0.0, 0.0, 0.0, 1.0));
+
+        // A red quad over the right half, then a white line across the middle row behind it that
+        // runs off the left edge
+        let quad = [
+            0.0, -1.0, 0.0, 1.0, 0.0, 0.0, //
+            1.0, -1.0, 0.0, 1.0, 0.0, 0.0, //
+            1.0, 1.0, 0.0, 1.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0, 1.0, 0.0, 0.0, //
+        ];
+        target.draw_triangles(
+            &quad,
+            &VertexLayout::position_color(),
+            &[0, 1, 2, 2, 3, 0],
+            DrawState::default(),
+            pass_through,
+            opaque,
+        );
+        let line = [
+            -3.0, 0.1, 0.5, 1.0, 1.0, 1.0, //
+            0.99, 0.1, 0.5, 1.0, 1.0, 1.0, //
+        ];
+        target.draw_lines(
+            &line,
+            &VertexLayout::position_color(),
+            false,
+            DrawState::default(),
+            pass_through,
+            opaque,
+        );
+
+        assert_eq!(pixel(&target, 0, 3), [255, 255, 255, 255]);
+        assert_eq!(pixel(&target, 3, 3), [255, 255, 255, 255]);
+        assert_eq!(pixel(&target, 6, 3), [255, 0, 0, 255]);
+        assert_eq!(pixel(&target, 0, 6), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn clips_triangles_behind_the_camera() {
+        let mut target = SoftwareTarget::new(8, 8);
+        target.begin_frame((0.0, 0.0, 0.0, 1.0));
+
+        // A floor triangle under the eye with its far corner in front and the other behind
+        let projection = nalgebra_glm::perspective(1.0, std::f32::consts::FRAC_PI_2, 0.1, 10.0);
+        let vertices = [
+            -1.0, -1.0, -4.0, 1.0, 1.0, 1.0, //
+            1.0, -1.0, -4.0, 1.0, 1.0, 1.0, //
+            0.0, -1.0, 4.0, 1.0, 1.0, 1.0, //
+        ];
+        target.draw_triangles(
+            &vertices,
+            &VertexLayout::position_color(),
+            &[0, 1, 2],
+            DrawState::default(),
+            |vertex| {
+                (
+                    projection * Vec4::new(vertex[0], vertex[1], vertex[2], 1.0),
+                    Vec3::new(vertex[3], vertex[4], vertex[5]),
+                )
+            },
+            opaque,
+        );
+
+        assert_eq!(pixel(&target, 4, 7), [255, 255, 255, 255]);
+        assert_eq!(pixel(&target, 4, 0), [0, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,677 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use js_sys::{Float32Array, Uint32Array};
+use nalgebra_glm::{Mat4x4, Vec2, Vec3};
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, ImageData, WebGl2RenderingContext, WebGlBuffer,
+    WebGlProgram, WebGlShader, WebGlTexture, WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+use crate::resize::display_aspect;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RenderError {
+    // The browser or GPU offers no WebGL2 context
+    Unsupported,
+    // GL gave no object back, usually because the context was lost
+    CreateFailed(&'static str),
+    Compile { stage: &'static str, log: String },
+    Link { log: String },
+    Incomplete,
+    ReadPixels,
+    PutPixels,
+    TooLarge { width: u32, height: u32, max: u32 },
+}
+
+impl RenderError {
+    // The driver's info log for shader errors
+    pub fn log(&self) -> Option<&str> {
+        match self {
+            Self::Compile { log, .. } | Self::Link { log } => Some(log),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "WebGL2 is not available"),
+            Self::CreateFailed(object) => write!(f, "Unable to create a {}", object),
+            Self::Compile { stage, .. } => write!(f, "The {} shader didn't compile", stage),
+            Self::Link { .. } => write!(f, "The shader program didn't link"),
+            Self::Incomplete => write!(f, "Couldn't create an offscreen framebuffer"),
+            Self::ReadPixels => write!(f, "Couldn't read the rendered pixels"),
+            Self::PutPixels => write!(f, "Couldn't draw the pixels to the canvas"),
+            Self::TooLarge { width, height, max } => write!(
+                f,
+                "{}×{} is larger than the {} pixels this GPU allows",
+                width, height, max
+            ),
+        }
+    }
+}
+
+// A canvas with its WebGL2 context
+pub struct RenderTarget {
+    canvas: HtmlCanvasElement,
+    gl: WebGl2RenderingContext,
+    // Size of the framebuffer being captured into, if any
+    offscreen: Cell<Option<(u32, u32)>>,
+}
+
+impl RenderTarget {
+    pub fn new(canvas: HtmlCanvasElement) -> Result<Self, RenderError> {
+        let gl = canvas
+            .get_context("webgl2")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<WebGl2RenderingContext>().ok())
+            .ok_or(RenderError::Unsupported)?;
+
+        Ok(Self {
+            canvas,
+            gl,
+            offscreen: Cell::new(None),
+        })
+    }
+
+    pub fn gl(&self) -> &WebGl2RenderingContext {
+        &self.gl
+    }
+
+    // Size in pixels of what's being drawn to
+    pub fn size(&self) -> (u32, u32) {
+        self.offscreen
+            .get()
+            .unwrap_or_else(|| (self.canvas.width(), self.canvas.height()))
+    }
+
+    // Width over height as displayed, or of the capture in progress
+    pub fn aspect(&self) -> f32 {
+        match self.offscreen.get() {
+            Some((width, height)) => width as f32 / height.max(1) as f32,
+            None => display_aspect(&self.canvas),
+        }
+    }
+
+    // Largest width or height capture can render at
+    pub fn max_size(&self) -> u32 {
+        self.gl
+            .get_parameter(WebGl2RenderingContext::MAX_RENDERBUFFER_SIZE)
+            .ok()
+            .and_then(|size| size.as_f64())
+            .map_or(4096, |size| size as u32)
+    }
+
+    // Covers the whole backing store and clears color and depth
+    pub fn begin_frame(&self, (r, g, b, a): (f32, f32, f32, f32)) {
+        let gl = &self.gl;
+        let (width, height) = self.size();
+
+        gl.viewport(0, 0, width as i32, height as i32);
+        gl.clear_color(r, g, b, a);
+        gl.clear(
+            WebGl2RenderingContext::COLOR_BUFFER_BIT | WebGl2RenderingContext::DEPTH_BUFFER_BIT,
+        );
+    }
+
+    // Runs draw against an offscreen framebuffer of the given size instead of the canvas and
+    // returns the RGBA pixels, rows from the top
+    pub fn capture(
+        &self,
+        width: u32,
+        height: u32,
+        draw: impl FnOnce(),
+    ) -> Result<Vec<u8>, RenderError> {
+        let max_size = self.max_size();
+        if width > max_size || height > max_size {
+            return Err(RenderError::TooLarge {
+                width,
+                height,
+                max: max_size,
+            });
+        }
+
+        let gl = &self.gl;
+        let renderbuffer = |format| {
+            let buffer = gl.create_renderbuffer();
+            gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, buffer.as_ref());
+            gl.renderbuffer_storage(
+                WebGl2RenderingContext::RENDERBUFFER,
+                format,
+                width as i32,
+                height as i32,
+            );
+            buffer
+        };
+        let color = renderbuffer(WebGl2RenderingContext::RGBA8);
+        let depth = renderbuffer(WebGl2RenderingContext::DEPTH_COMPONENT24);
+
+        let framebuffer = gl.create_framebuffer();
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, framebuffer.as_ref());
+        gl.framebuffer_renderbuffer(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::COLOR_ATTACHMENT0,
+            WebGl2RenderingContext::RENDERBUFFER,
+            color.as_ref(),
+        );
+        gl.framebuffer_renderbuffer(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::DEPTH_ATTACHMENT,
+            WebGl2RenderingContext::RENDERBUFFER,
+            depth.as_ref(),
+        );
+
+        let result = if gl.check_framebuffer_status(WebGl2RenderingContext::FRAMEBUFFER)
+            == WebGl2RenderingContext::FRAMEBUFFER_COMPLETE
+        {
+            self.offscreen.set(Some((width, height)));
+            draw();
+            self.offscreen.set(None);
+
+            self.read_pixels(width, height)
+        } else {
+            Err(RenderError::Incomplete)
+        };
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        gl.delete_framebuffer(framebuffer.as_ref());
+        gl.delete_renderbuffer(color.as_ref());
+        gl.delete_renderbuffer(depth.as_ref());
+
+        result
+    }
+
+    fn read_pixels(&self, width: u32, height: u32) -> Result<Vec<u8>, RenderError> {
+        let mut pixels = vec![0; (width * height * 4) as usize];
+        self.gl
+            .read_pixels_with_opt_u8_array(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                WebGl2RenderingContext::RGBA,
+                WebGl2RenderingContext::UNSIGNED_BYTE,
+                Some(&mut pixels),
+            )
+            .map_err(|_| RenderError::ReadPixels)?;
+
+        // GL rows start at the bottom
+        let row = (width * 4) as usize;
+        Ok(pixels.chunks_exact(row).rev().flatten().copied().collect())
+    }
+}
+
+pub fn context_2d(canvas: &HtmlCanvasElement) -> Result<CanvasRenderingContext2d, RenderError> {
+    canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or(RenderError::CreateFailed("2D context"))
+}
+
+// Draws RGBA pixels, rows from the top, at the canvas's top left corner
+pub fn put_pixels(
+    context: &CanvasRenderingContext2d,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> Result<(), RenderError> {
+    let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(rgba), width, height)
+        .map_err(|_| RenderError::CreateFailed("image data"))?;
+
+    context
+        .put_image_data(&data, 0.0, 0.0)
+        .map_err(|_| RenderError::PutPixels)
+}
+
+pub trait Uniform {
+    fn set(&self, gl: &WebGl2RenderingContext, location: Option<&WebGlUniformLocation>);
+}
+
+impl Uniform for f32 {
+    fn set(&self, gl: &WebGl2RenderingContext, location: Option<&WebGlUniformLocation>) {
+        gl.uniform1f(location, *self);
+    }
+}
+
+impl Uniform for i32 {
+    fn set(&self, gl: &WebGl2RenderingContext, location: Option<&WebGlUniformLocation>) {
+        gl.uniform1i(location, *self);
+    }
+}
+
+impl Uniform for bool {
+    fn set(&self, gl: &WebGl2RenderingContext, location: Option<&WebGlUniformLocation>) {
+        gl.uniform1i(location, *self as i32);
+    }
+}
+
+impl Uniform for Vec2 {
+    fn set(&self, gl: &WebGl2RenderingContext, location: Option<&WebGlUniformLocation>) {
+        gl.uniform2f(location, self.x, self.y);
+    }
+}
+
+impl Uniform for Vec3 {
+    fn set(&self, gl: &WebGl2RenderingContext, location: Option<&WebGlUniformLocation>) {
+        gl.uniform3f(location, self.x, self.y, self.z);
+    }
+}
+
+impl Uniform for Mat4x4 {
+    fn set(&self, gl: &WebGl2RenderingContext, location: Option<&WebGlUniformLocation>) {
+        gl.uniform_matrix4fv_with_f32_array(location, false, self.as_slice());
+    }
+}
+
+pub struct ShaderProgram {
+    program: WebGlProgram,
+    // Looked up on first use
+    uniforms: RefCell<HashMap<&'static str, Option<WebGlUniformLocation>>>,
+}
+
+impl ShaderProgram {
+    pub fn new(
+        gl: &WebGl2RenderingContext,
+        vert_source: &str,
+        frag_source: &str,
+    ) -> Result<Self, RenderError> {
+        let vert_shader = compile_shader(gl, vert_source, WebGl2RenderingContext::VERTEX_SHADER)?;
+        let frag_shader = compile_shader(gl, frag_source, WebGl2RenderingContext::FRAGMENT_SHADER)?;
+        let program = link_program(gl, &vert_shader, &frag_shader)?;
+
+        Ok(Self {
+            program,
+            uniforms: RefCell::default(),
+        })
+    }
+
+    pub fn use_program(&self, gl: &WebGl2RenderingContext) {
+        gl.use_program(Some(&self.program));
+    }
+
+    // The program must be in use
+    pub fn set_uniform(
+        &self,
+        gl: &WebGl2RenderingContext,
+        name: &'static str,
+        value: &impl Uniform,
+    ) {
+        let mut uniforms = self.uniforms.borrow_mut();
+        let location = uniforms
+            .entry(name)
+            .or_insert_with(|| gl.get_uniform_location(&self.program, name));
+
+        value.set(gl, location.as_ref());
+    }
+
+    fn attribute_location(&self, gl: &WebGl2RenderingContext, name: &str) -> Option<u32> {
+        let location = gl.get_attrib_location(&self.program, name);
+
+        (location >= 0).then_some(location as u32)
+    }
+}
+
+// Float attributes interleaved in one buffer, in order
+#[derive(Clone)]
+pub struct VertexLayout {
+    attributes: Vec<(&'static str, i32)>,
+}
+
+impl VertexLayout {
+    pub fn new(attributes: &[(&'static str, i32)]) -> Self {
+        Self {
+            attributes: attributes.to_vec(),
+        }
+    }
+
+    // Three floats of position followed by three of color
+    pub fn position_color() -> Self {
+        Self::new(&[("a_position", 3), ("a_color", 3)])
+    }
+
+    pub fn floats_per_vertex(&self) -> usize {
+        self.attributes.iter().map(|(_, size)| *size as usize).sum()
+    }
+
+    fn stride(&self) -> i32 {
+        (self.floats_per_vertex() * 4) as i32
+    }
+}
+
+pub struct Mesh {
+    va: Option<WebGlVertexArrayObject>,
+    vertex_buffer: Option<WebGlBuffer>,
+    index_buffer: Option<WebGlBuffer>,
+    instance_buffer: Option<WebGlBuffer>,
+    layout: VertexLayout,
+    instance_layout: Option<VertexLayout>,
+    mode: u32,
+    vertex_count: i32,
+    index_count: Option<i32>,
+    instance_count: i32,
+    // Last shared vertex data uploaded, to skip uploading it again
+    shared: Rc<Vec<f32>>,
+}
+
+impl Mesh {
+    // Mode is the primitive drawn, like WebGl2RenderingContext::TRIANGLES
+    pub fn new(
+        gl: &WebGl2RenderingContext,
+        program: &ShaderProgram,
+        layout: VertexLayout,
+        mode: u32,
+    ) -> Self {
+        let va = gl.create_vertex_array();
+        gl.bind_vertex_array(va.as_ref());
+
+        let vertex_buffer = gl.create_buffer();
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, vertex_buffer.as_ref());
+        bind_attributes(gl, program, &layout, 0);
+
+        gl.bind_vertex_array(None);
+
+        Self {
+            va,
+            vertex_buffer,
+            index_buffer: None,
+            instance_buffer: None,
+            layout,
+            instance_layout: None,
+            mode,
+            vertex_count: 0,
+            index_count: None,
+            instance_count: 0,
+            shared: Rc::default(),
+        }
+    }
+
+    // A mesh drawn once per instance, with instance_layout's attributes advancing per instance
+    // instead of per vertex
+    pub fn new_instanced(
+        gl: &WebGl2RenderingContext,
+        program: &ShaderProgram,
+        layout: VertexLayout,
+        instance_layout: VertexLayout,
+        mode: u32,
+    ) -> Self {
+        let mut mesh = Self::new(gl, program, layout, mode);
+
+        gl.bind_vertex_array(mesh.va.as_ref());
+        mesh.instance_buffer = gl.create_buffer();
+        gl.bind_buffer(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            mesh.instance_buffer.as_ref(),
+        );
+        bind_attributes(gl, program, &instance_layout, 1);
+        gl.bind_vertex_array(None);
+
+        mesh.instance_layout = Some(instance_layout);
+        mesh
+    }
+
+    pub fn set_vertices(&mut self, gl: &WebGl2RenderingContext, vertices: &[f32]) {
+        gl.bind_buffer(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            self.vertex_buffer.as_ref(),
+        );
+        gl.buffer_data_with_array_buffer_view(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            &Float32Array::from(vertices),
+            WebGl2RenderingContext::STATIC_DRAW,
+        );
+
+        self.vertex_count = (vertices.len() / self.layout.floats_per_vertex()) as i32;
+        self.shared = Rc::default();
+    }
+
+    // Uploads vertex data shared with the rest of the app as place transforms it, unless it's the
+    // same data as last time
+    pub fn set_shared_vertices(
+        &mut self,
+        gl: &WebGl2RenderingContext,
+        vertices: &Rc<Vec<f32>>,
+        place: impl FnOnce(&[f32]) -> Vec<f32>,
+    ) {
+        if Rc::ptr_eq(&self.shared, vertices) {
+            return;
+        }
+
+        self.set_vertices(gl, &place(vertices));
+        self.shared = vertices.clone();
+    }
+
+    // Makes the next set_shared_vertices upload even if the data is the same, for when the way it's
+    // placed changed
+    pub fn forget_shared(&mut self) {
+        self.shared = Rc::default();
+    }
+
+    pub fn set_indices(&mut self, gl: &WebGl2RenderingContext, indices: &[u32]) {
+        if self.index_buffer.is_none() {
+            self.index_buffer = gl.create_buffer();
+        }
+
+        // The element buffer binding is part of the vertex array
+        gl.bind_vertex_array(self.va.as_ref());
+        gl.bind_buffer(
+            WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+            self.index_buffer.as_ref(),
+        );
+        gl.buffer_data_with_array_buffer_view(
+            WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+            &Uint32Array::from(indices),
+            WebGl2RenderingContext::STATIC_DRAW,
+        );
+        gl.bind_vertex_array(None);
+
+        self.index_count = Some(indices.len() as i32);
+    }
+
+    pub fn set_instances(&mut self, gl: &WebGl2RenderingContext, instances: &[f32]) {
+        let floats_per_instance = match &self.instance_layout {
+            Some(layout) => layout.floats_per_vertex(),
+            None => return,
+        };
+
+        gl.bind_buffer(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            self.instance_buffer.as_ref(),
+        );
+        gl.buffer_data_with_array_buffer_view(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            &Float32Array::from(instances),
+            WebGl2RenderingContext::STATIC_DRAW,
+        );
+
+        self.instance_count = (instances.len() / floats_per_instance) as i32;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let instanced_empty = self.instance_layout.is_some() && self.instance_count == 0;
+
+        instanced_empty || self.index_count.unwrap_or(self.vertex_count) == 0
+    }
+
+    // The mesh's program must be in use with its uniforms set
+    pub fn draw(&self, gl: &WebGl2RenderingContext) {
+        if self.is_empty() {
+            return;
+        }
+
+        gl.bind_vertex_array(self.va.as_ref());
+        match (self.index_count, self.instance_layout.is_some()) {
+            (Some(count), false) => {
+                gl.draw_elements_with_i32(self.mode, count, WebGl2RenderingContext::UNSIGNED_INT, 0)
+            }
+            (Some(count), true) => gl.draw_elements_instanced_with_i32(
+                self.mode,
+                count,
+                WebGl2RenderingContext::UNSIGNED_INT,
+                0,
+                self.instance_count,
+            ),
+            (None, false) => gl.draw_arrays(self.mode, 0, self.vertex_count),
+            (None, true) => {
+                gl.draw_arrays_instanced(self.mode, 0, self.vertex_count, self.instance_count)
+            }
+        }
+        gl.bind_vertex_array(None);
+    }
+}
+
+// An RGBA cube of texels sampled with linear filtering, clamped at its edges
+pub struct VolumeTexture {
+    texture: Option<WebGlTexture>,
+}
+
+impl VolumeTexture {
+    pub fn new(gl: &WebGl2RenderingContext) -> Self {
+        let texture = gl.create_texture();
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_3D, texture.as_ref());
+        for (parameter, value) in [
+            (
+                WebGl2RenderingContext::TEXTURE_MIN_FILTER,
+                WebGl2RenderingContext::LINEAR,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_MAG_FILTER,
+                WebGl2RenderingContext::LINEAR,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_WRAP_S,
+                WebGl2RenderingContext::CLAMP_TO_EDGE,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_WRAP_T,
+                WebGl2RenderingContext::CLAMP_TO_EDGE,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_WRAP_R,
+                WebGl2RenderingContext::CLAMP_TO_EDGE,
+            ),
+        ] {
+            gl.tex_parameteri(WebGl2RenderingContext::TEXTURE_3D, parameter, value as i32);
+        }
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_3D, None);
+
+        Self { texture }
+    }
+
+    // Texels are RGBA bytes, x fastest then y then z
+    pub fn set_texels(&self, gl: &WebGl2RenderingContext, size: u32, texels: &[u8]) {
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_3D, self.texture.as_ref());
+        gl.tex_image_3d_with_opt_u8_array(
+            WebGl2RenderingContext::TEXTURE_3D,
+            0,
+            WebGl2RenderingContext::RGBA8 as i32,
+            size as i32,
+            size as i32,
+            size as i32,
+            0,
+            WebGl2RenderingContext::RGBA,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            Some(texels),
+        )
+        .ok();
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_3D, None);
+    }
+
+    // Binds to a texture unit, set the sampler uniform to the same unit
+    pub fn bind(&self, gl: &WebGl2RenderingContext, unit: u32) {
+        gl.active_texture(WebGl2RenderingContext::TEXTURE0 + unit);
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_3D, self.texture.as_ref());
+    }
+}
+
+// Points the layout's attributes into the bound array buffer, a divisor of 1 advances them per
+// instance
+fn bind_attributes(
+    gl: &WebGl2RenderingContext,
+    program: &ShaderProgram,
+    layout: &VertexLayout,
+    divisor: u32,
+) {
+    let mut offset = 0;
+    for (name, size) in &layout.attributes {
+        // Attributes the shader doesn't use are optimized away
+        if let Some(location) = program.attribute_location(gl, name) {
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(
+                location,
+                *size,
+                WebGl2RenderingContext::FLOAT,
+                false,
+                layout.stride(),
+                offset,
+            );
+            gl.vertex_attrib_divisor(location, divisor);
+        }
+        offset += size * 4;
+    }
+}
+
+fn compile_shader(
+    gl: &WebGl2RenderingContext,
+    source: &str,
+    shader_type: u32,
+) -> Result<WebGlShader, RenderError> {
+    let shader = gl
+        .create_shader(shader_type)
+        .ok_or(RenderError::CreateFailed("shader"))?;
+
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+
+    if gl
+        .get_shader_parameter(&shader, WebGl2RenderingContext::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        let stage = if shader_type == WebGl2RenderingContext::VERTEX_SHADER {
+            "vertex"
+        } else {
+            "fragment"
+        };
+
+        Err(RenderError::Compile {
+            stage,
+            log: gl.get_shader_info_log(&shader).unwrap_or_default(),
+        })
+    }
+}
+
+fn link_program(
+    gl: &WebGl2RenderingContext,
+    vert_shader: &WebGlShader,
+    frag_shader: &WebGlShader,
+) -> Result<WebGlProgram, RenderError> {
+    let program = gl
+        .create_program()
+        .ok_or(RenderError::CreateFailed("program"))?;
+
+    gl.attach_shader(&program, vert_shader);
+    gl.attach_shader(&program, frag_shader);
+    gl.link_program(&program);
+
+    if gl
+        .get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        Err(RenderError::Link {
+            log: gl.get_program_info_log(&program).unwrap_or_default(),
+        })
+    }
+}
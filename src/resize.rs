@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use gloo_events::EventListener;
+use js_sys::{Array, Function};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, Element, HtmlCanvasElement};
+use yew::Callback;
+
+// web-sys only exposes ResizeObserver behind the unstable APIs flag, the three methods used here
+// have been stable in browsers for years
+#[wasm_bindgen]
+extern "C" {
+    type ResizeObserver;
+
+    #[wasm_bindgen(constructor)]
+    fn new(callback: &Function) -> ResizeObserver;
+
+    #[wasm_bindgen(method)]
+    fn observe(this: &ResizeObserver, target: &Element);
+
+    #[wasm_bindgen(method)]
+    fn disconnect(this: &ResizeObserver);
+}
+
+// Calls back whenever any of the elements changes size or the device pixel ratio changes, until
+// dropped
+pub struct ResizeWatcher {
+    observer: ResizeObserver,
+    _closure: Closure<dyn FnMut(Array)>,
+    _ratio_listener: Rc<RefCell<Option<EventListener>>>,
+}
+
+impl ResizeWatcher {
+    pub fn new(elements: &[Element], callback: Callback<()>) -> Self {
+        let ratio_listener = Rc::new(RefCell::new(None));
+        watch_pixel_ratio(Rc::downgrade(&ratio_listener), callback.clone());
+
+        let closure =
+            Closure::wrap(Box::new(move |_: Array| callback.emit(())) as Box<dyn FnMut(Array)>);
+        let observer = ResizeObserver::new(closure.as_ref().unchecked_ref());
+
+        for element in elements {
+            observer.observe(element);
+        }
+
+        Self {
+            observer,
+            _closure: closure,
+            _ratio_listener: ratio_listener,
+        }
+    }
+}
+
+// Moving the window to another screen changes the pixel ratio without resizing anything. A media
+// query only matches the ratio it was made for, so each change re-arms it with the new one
+fn watch_pixel_ratio(slot: Weak<RefCell<Option<EventListener>>>, callback: Callback<()>) {
+    let window = match window() {
+        Some(window) => window,
+        None => return,
+    };
+    let query = format!("(resolution: {}dppx)", window.device_pixel_ratio());
+    let list = match window.match_media(&query) {
+        Ok(Some(list)) => list,
+        _ => return,
+    };
+    let listener = {
+        let slot = slot.clone();
+        EventListener::once(&list, "change", move |_| {
+            callback.emit(());
+            watch_pixel_ratio(slot, callback);
+        })
+    };
+
+    if let Some(slot) = slot.upgrade() {
+        *slot.borrow_mut() = Some(listener);
+    }
+}
+
+impl Drop for ResizeWatcher {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+// Sizes the backing store to the canvas's displayed size in device pixels, returns whether it
+// changed
+pub fn fit_canvas_to_display(canvas: &HtmlCanvasElement) -> bool {
+    let ratio = window()
+        .map(|window| window.device_pixel_ratio())
+        .unwrap_or(1.0);
+    let width = ((canvas.client_width() as f64 * ratio).round() as u32).max(1);
+    let height = ((canvas.client_height() as f64 * ratio).round() as u32).max(1);
+
+    if canvas.width() == width && canvas.height() == height {
+        return false;
+    }
+
+    canvas.set_width(width);
+    canvas.set_height(height);
+    true
+}
+
+// Width over height of the canvas as displayed
+pub fn display_aspect(canvas: &HtmlCanvasElement) -> f32 {
+    canvas.client_width().max(1) as f32 / canvas.client_height().max(1) as f32
+}
@@ -0,0 +1,487 @@
+use std::rc::Rc;
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::canvas::{push_rgba, put_rgba, view_draw_error};
+use crate::color::rgb_to_cmyk;
+use crate::color_picker::CMYK;
+use crate::dot_gain::{cmyk_to_rgb_with_gain, DotGainCurves};
+use crate::image::LoadedImage;
+use crate::renderer::RenderError;
+
+pub const PLATES: [CMYK; 4] = [CMYK::Cyan, CMYK::Magenta, CMYK::Yellow, CMYK::Key];
+// A typical sheetfed press limit. The separation replaces all gray with black, so it never puts
+// down more than 300%
+const DEFAULT_INK_LIMIT: f32 = 2.8;
+
+pub struct Separation {
+    pub width: u32,
+    pub height: u32,
+    // None where the image is transparent, which prints nothing and isn't counted in the stats
+    pub cmyk: Vec<Option<(f32, f32, f32, f32)>>,
+}
+
+impl Separation {
+    pub fn new(image: &LoadedImage) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            cmyk: image
+                .pixel_colors()
+                .map(|rgb| rgb.map(separate_color))
+                .collect(),
+        }
+    }
+}
+
+// Black is separated as black ink alone rather than the four solids rgb_to_cmyk gives it
+fn separate_color(rgb: (f32, f32, f32)) -> (f32, f32, f32, f32) {
+    match rgb_to_cmyk(rgb) {
+        (_, _, _, k) if k >= 1.0 => (0.0, 0.0, 0.0, 1.0),
+        cmyk => cmyk,
+    }
+}
+
+pub fn plate_name(plate: CMYK) -> &'static str {
+    match plate {
+        CMYK::Cyan => "Cyan",
+        CMYK::Magenta => "Magenta",
+        CMYK::Yellow => "Yellow",
+        CMYK::Key => "Black",
+    }
+}
+
+pub fn ink_amount((c, m, y, k): (f32, f32, f32, f32), plate: CMYK) -> f32 {
+    match plate {
+        CMYK::Cyan => c,
+        CMYK::Magenta => m,
+        CMYK::Yellow => y,
+        CMYK::Key => k,
+    }
+}
+
+// Color of full coverage of a single ink printed on white paper
+pub fn ink_color(plate: CMYK) -> (f32, f32, f32) {
+    match plate {
+        CMYK::Cyan => (0.0, 1.0, 1.0),
+        CMYK::Magenta => (1.0, 0.0, 1.0),
+        CMYK::Yellow => (1.0, 1.0, 0.0),
+        CMYK::Key => (0.0, 0.0, 0.0),
+    }
+}
+
+pub fn render_plate(separation: &Separation, plate: CMYK, tinted: bool) -> Vec<u8> {
+    let ink = if tinted {
+        ink_color(plate)
+    } else {
+        (0.0, 0.0, 0.0)
+    };
+
+    let mut rgba = Vec::with_capacity(separation.cmyk.len() * 4);
+    for cmyk in &separation.cmyk {
+        let amount = cmyk.map_or(0.0, |cmyk| ink_amount(cmyk, plate));
+        let mix = |ink: f32| 1.0 - amount * (1.0 - ink);
+
+        push_rgba(&mut rgba, (mix(ink.0), mix(ink.1), mix(ink.2)));
+    }
+
+    rgba
+}
+
+pub fn render_composite(
+    separation: &Separation,
+    visible: &[bool; 4],
+    dot_gain: &DotGainCurves,
+) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(separation.cmyk.len() * 4);
+    for (c, m, y, k) in separation.cmyk.iter().map(|cmyk| cmyk.unwrap_or_default()) {
+        let shown = |amount: f32, index: usize| if visible[index] { amount } else { 0.0 };
+
+        push_rgba(
+            &mut rgba,
+            cmyk_to_rgb_with_gain(
+                (shown(c, 0), shown(m, 1), shown(y, 2), shown(k, 3)),
+                dot_gain,
+            ),
+        );
+    }
+
+    rgba
+}
+
+pub struct TacStats {
+    pub max: f32,
+    pub mean: f32,
+    // Fraction of pixels whose total area coverage exceeds the ink limit
+    pub over_limit: f32,
+}
+
+fn total_coverage((c, m, y, k): (f32, f32, f32, f32)) -> f32 {
+    c + m + y + k
+}
+
+pub fn ink_coverage_stats(separation: &Separation, limit: f32) -> TacStats {
+    let (mut max, mut sum, mut over, mut counted) = (0.0f32, 0.0f32, 0usize, 0usize);
+    for cmyk in separation.cmyk.iter().flatten() {
+        let tac = total_coverage(*cmyk);
+        max = max.max(tac);
+        sum += tac;
+        if tac > limit {
+            over += 1;
+        }
+        counted += 1;
+    }
+
+    let n = counted.max(1) as f32;
+    TacStats {
+        max,
+        mean: sum / n,
+        over_limit: over as f32 / n,
+    }
+}
+
+// Blue through green to yellow up to the limit, anything above it is flagged in magenta and
+// transparent pixels are left white
+pub fn render_tac_heatmap(separation: &Separation, limit: f32) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(separation.cmyk.len() * 4);
+    for cmyk in &separation.cmyk {
+        let tac = match cmyk {
+            Some(cmyk) => total_coverage(*cmyk),
+            None => {
+                push_rgba(&mut rgba, (1.0, 1.0, 1.0));
+                continue;
+            }
+        };
+        let color = if tac > limit {
+            (1.0, 0.0, 1.0)
+        } else {
+            let t = tac / limit.max(f32::EPSILON);
+            if t < 0.5 {
+                (0.0, t * 2.0, 1.0 - t * 2.0)
+            } else {
+                ((t - 0.5) * 2.0, 1.0, 0.0)
+            }
+        };
+
+        push_rgba(&mut rgba, color);
+    }
+
+    rgba
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub image: Option<Rc<LoadedImage>>,
+    pub dot_gain: DotGainCurves,
+}
+
+pub enum Msg {
+    TogglePlate(usize),
+    ToggleTinted,
+    InkLimitChanged(f32),
+    // Result of the last draw, when it differs from the one shown
+    Drawn(Option<RenderError>),
+}
+
+pub struct SeparationPanel {
+    image: Option<Rc<LoadedImage>>,
+    separation: Option<Separation>,
+    // Curves the composite was last drawn with
+    dot_gain: DotGainCurves,
+    // Coverage of the separation at the current ink limit
+    stats: Option<TacStats>,
+    // Whether the plates and composite are out of date with the separation and settings
+    redraw: bool,
+    // The heatmap only depends on the separation and the ink limit
+    redraw_heatmap: bool,
+    visible: [bool; 4],
+    tinted: bool,
+    ink_limit: f32,
+    plates: [NodeRef; 4],
+    composite: NodeRef,
+    heatmap: NodeRef,
+    draw_error: Option<RenderError>,
+}
+
+impl SeparationPanel {
+    // Returns whether the image is a different one
+    fn separate(&mut self, image: Option<Rc<LoadedImage>>) -> bool {
+        let unchanged = match (&self.image, &image) {
+            (Some(current), Some(image)) => Rc::ptr_eq(current, image),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return false;
+        }
+
+        self.separation = image.as_deref().map(Separation::new);
+        self.image = image;
+        self.redraw = true;
+        self.measure();
+
+        true
+    }
+
+    // Draws whichever canvases are out of date
+    fn draw(&mut self) -> Result<(), RenderError> {
+        let separation = match &self.separation {
+            Some(separation) => separation,
+            None => return Ok(()),
+        };
+        let (width, height) = (separation.width, separation.height);
+
+        if self.redraw_heatmap {
+            self.redraw_heatmap = false;
+            put_rgba(
+                &self.heatmap,
+                width,
+                height,
+                &render_tac_heatmap(separation, self.ink_limit),
+            )?;
+        }
+
+        if !self.redraw {
+            return Ok(());
+        }
+        self.redraw = false;
+
+        for (index, plate) in PLATES.iter().enumerate() {
+            let rgba = if self.visible[index] {
+                render_plate(separation, *plate, self.tinted)
+            } else {
+                vec![255; separation.cmyk.len() * 4]
+            };
+
+            put_rgba(&self.plates[index], width, height, &rgba)?;
+        }
+
+        put_rgba(
+            &self.composite,
+            width,
+            height,
+            &render_composite(separation, &self.visible, &self.dot_gain),
+        )
+    }
+
+    fn measure(&mut self) {
+        self.stats = self
+            .separation
+            .as_ref()
+            .map(|separation| ink_coverage_stats(separation, self.ink_limit));
+        self.redraw_heatmap = true;
+    }
+}
+
+impl Component for SeparationPanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut panel = Self {
+            image: None,
+            separation: None,
+            dot_gain: ctx.props().dot_gain.clone(),
+            stats: None,
+            redraw: false,
+            redraw_heatmap: false,
+            visible: [true; 4],
+            tinted: false,
+            ink_limit: DEFAULT_INK_LIMIT,
+            plates: Default::default(),
+            composite: NodeRef::default(),
+            heatmap: NodeRef::default(),
+            draw_error: None,
+        };
+        panel.separate(ctx.props().image.clone());
+
+        panel
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let stats = match &self.stats {
+            Some(stats) => stats,
+            None => return html! {},
+        };
+
+        let on_ink_limit_change = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::InkLimitChanged(input.value_as_number() as f32 / 100.0)
+        });
+
+        let plates = PLATES.iter().enumerate().map(|(index, plate)| {
+            html! {
+                <div class="col-auto text-center">
+                    <canvas style="width: 160px;" ref={self.plates[index].clone()} />
+                    <div>
+                        <input
+                            type="checkbox"
+                            checked={self.visible[index]}
+                            onchange={ctx.link().callback(move |_| Msg::TogglePlate(index))} />
+                        <label>{plate_name(*plate)}</label>
+                    </div>
+                </div>
+            }
+        });
+
+        html! {
+            <div class="container">
+                <div class="row">
+                    <div class="col">
+                        <label>{"Separations "}</label>
+                        <input
+                            type="checkbox"
+                            checked={self.tinted}
+                            onchange={ctx.link().callback(|_| Msg::ToggleTinted)} />
+                        <label>{"Tinted plates"}</label>
+                    </div>
+                </div>
+                <div class="row">
+                    { for plates }
+                    <div class="col-auto text-center">
+                        <canvas style="width: 160px;" ref={self.composite.clone()} />
+                        <div><label>{"Composite"}</label></div>
+                    </div>
+                    <div class="col-auto text-center">
+                        <canvas style="width: 160px;" ref={self.heatmap.clone()} />
+                        <div><label>{"Total ink coverage"}</label></div>
+                    </div>
+                </div>
+                <div class="row">
+                    <div class="col">
+                        <label>{"Ink limit: "}</label>
+                        <input
+                            type="range"
+                            min="0"
+                            max="400"
+                            step="5"
+                            value={(self.ink_limit * 100.0).to_string()}
+                            oninput={on_ink_limit_change} />
+                        <span>{format!("{:.0}%", self.ink_limit * 100.0)}</span>
+                    </div>
+                    <div class="col">
+                        <span>{format!("Max TAC: {:.1}%", stats.max * 100.0)}</span>
+                        <span>{format!(" | Mean TAC: {:.1}%", stats.mean * 100.0)}</span>
+                        <span>{format!(" | Over limit: {:.2}%", stats.over_limit * 100.0)}</span>
+                    </div>
+                </div>
+                { view_draw_error(&self.draw_error) }
+            </div>
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::TogglePlate(index) => {
+                self.visible[index] = !self.visible[index];
+                self.redraw = true;
+            }
+            Msg::ToggleTinted => {
+                self.tinted = !self.tinted;
+                self.redraw = true;
+            }
+            Msg::InkLimitChanged(limit) => {
+                self.ink_limit = limit;
+                self.measure();
+            }
+            Msg::Drawn(error) => {
+                self.draw_error = error;
+            }
+        }
+
+        true
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let regained = ctx.props().dot_gain != self.dot_gain;
+        if regained {
+            self.dot_gain = ctx.props().dot_gain.clone();
+            self.redraw = true;
+        }
+
+        self.separate(ctx.props().image.clone()) || regained
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if !self.redraw && !self.redraw_heatmap {
+            return;
+        }
+
+        let drawn = self.draw().err();
+        if drawn != self.draw_error {
+            ctx.link().send_message(Msg::Drawn(drawn));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn separation(rgb: &[(f32, f32, f32)]) -> Separation {
+        Separation {
+            width: rgb.len() as u32,
+            height: 1,
+            cmyk: rgb.iter().map(|rgb| Some(separate_color(*rgb))).collect(),
+        }
+    }
+
+    #[test]
+    fn black_and_near_black_are_black_ink_only() {
+        let near_black = 1.0 / 255.0;
+        let stats = ink_coverage_stats(
+            &separation(&[(0.0, 0.0, 0.0), (near_black, near_black, near_black)]),
+            3.0,
+        );
+
+        assert!((stats.max - 1.0).abs() < 1e-6);
+        assert!((stats.mean - (2.0 - near_black) / 2.0).abs() < 1e-6);
+        assert_eq!(stats.over_limit, 0.0);
+    }
+
+    #[test]
+    fn rich_black_is_flagged_over_the_limit() {
+        let rich_black = Separation {
+            width: 2,
+            height: 1,
+            cmyk: vec![Some((0.6, 0.5, 0.5, 1.0)), Some((0.0, 0.0, 0.0, 1.0))],
+        };
+
+        let stats = ink_coverage_stats(&rich_black, 2.4);
+        assert!((stats.max - 2.6).abs() < 1e-6);
+        assert_eq!(stats.over_limit, 0.5);
+        assert_eq!(
+            &render_tac_heatmap(&rich_black, 2.4)[..4],
+            &[255, 0, 255, 255]
+        );
+
+        assert_eq!(ink_coverage_stats(&rich_black, 3.0).over_limit, 0.0);
+    }
+
+    #[test]
+    fn dark_saturated_colors_go_over_the_default_limit() {
+        // Magenta and yellow solids over 90% black
+        let stats = ink_coverage_stats(&separation(&[(0.1, 0.0, 0.0)]), DEFAULT_INK_LIMIT);
+
+        assert!((stats.max - 2.9).abs() < 1e-6);
+        assert_eq!(stats.over_limit, 1.0);
+    }
+
+    #[test]
+    fn transparent_pixels_print_nothing_and_are_not_counted() {
+        // Opaque cyan next to a transparent pixel that stores black
+        let image = LoadedImage {
+            width: 2,
+            height: 1,
+            pixels: vec![0, 255, 255, 255, 0, 0, 0, 0],
+        };
+        let separation = Separation::new(&image);
+
+        let stats = ink_coverage_stats(&separation, 3.0);
+        assert_eq!((stats.max, stats.mean), (1.0, 1.0));
+        assert_eq!(&render_plate(&separation, CMYK::Key, false)[4..], &[255; 4]);
+        assert_eq!(image.rgb_pixels().count(), 1);
+    }
+}
@@ -0,0 +1,602 @@
+use std::f32::consts::TAU;
+
+use nalgebra::{Matrix3, Vector3};
+use nalgebra_glm::Vec3;
+
+use crate::color::{clamp_rgb, lab_to_rgb, oklab_to_rgb, rgb_to_lab, rgb_to_oklab};
+use crate::cube::{rgb_to_cube_position, CUBE_INDICES, CUBE_VERTICES};
+
+// Quads along each edge of a face of the RYB cube, its colors aren't linear in position
+const RYB_SUBDIVISIONS: u32 = 8;
+// The gamut hulls bend a lot more
+const HULL_SUBDIVISIONS: u32 = 24;
+// Quads around the round solids
+const SEGMENTS: u32 = 48;
+const RYB_INVERSE_ITERATIONS: usize = 16;
+// Texels along each edge of a ColorVolume
+pub const VOLUME_SIZE: u32 = 64;
+// Half the side of the box a ColorVolume covers, the hulls reach a little past the cube
+pub const VOLUME_EXTENT: f32 = 0.6;
+// Colors on a solid's surface count as inside it despite rounding
+const SURFACE_TOLERANCE: f32 = 1e-4;
+// The hulls stand lightness upright with a toward +x and b toward -z, scaled to about the cube's
+// size.
+const LAB_CHROMA_SCALE: f32 = 1.0 / 200.0;
+const OKLAB_CHROMA_SCALE: f32 = 1.6;
+
+// RGB of the RYB cube's corners, indexed by red + yellow * 2 + blue * 4, after Gossett and Chen
+const RYB_CORNERS: [(f32, f32, f32); 8] = [
+    (1.0, 1.0, 1.0),
+    (1.0, 0.0, 0.0),
+    (1.0, 1.0, 0.0),
+    (1.0, 0.5, 0.0),
+    (0.163, 0.373, 0.6),
+    (0.5, 0.0, 0.5),
+    (0.0, 0.66, 0.2),
+    (0.2, 0.094, 0.0),
+];
+const RYB_CORNER_NAMES: [&str; 8] = [
+    "White", "Red", "Yellow", "Orange", "Blue", "Purple", "Green", "Black",
+];
+const RGB_CORNERS: [(&str, (f32, f32, f32)); 8] = [
+    ("Black", (0.0, 0.0, 0.0)),
+    ("Red", (1.0, 0.0, 0.0)),
+    ("Green", (0.0, 1.0, 0.0)),
+    ("Blue", (0.0, 0.0, 1.0)),
+    ("Cyan", (0.0, 1.0, 1.0)),
+    ("Magenta", (1.0, 0.0, 1.0)),
+    ("Yellow", (1.0, 1.0, 0.0)),
+    ("White", (1.0, 1.0, 1.0)),
+];
+
+// Shapes the color space is shown as. The cubes lay their own three coordinates out on the axes
+// the RGB cube uses for red, green and blue, the round solids stand value or lightness upright
+// with hue around it. The hulls are the sRGB cube bent into perceptual coordinates.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Solid {
+    RgbCube,
+    CmyCube,
+    RybCube,
+    HsvHexcone,
+    HsvCylinder,
+    HslBicone,
+    LabHull,
+    OklabHull,
+}
+
+pub const SOLIDS: [Solid; 8] = [
+    Solid::RgbCube,
+    Solid::CmyCube,
+    Solid::RybCube,
+    Solid::HsvHexcone,
+    Solid::HsvCylinder,
+    Solid::HslBicone,
+    Solid::LabHull,
+    Solid::OklabHull,
+];
+
+// Hue in 0..1 starting at red, 0 for grays
+fn hue((r, g, b): (f32, f32, f32)) -> f32 {
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    if chroma <= 0.0 {
+        return 0.0;
+    }
+
+    let sector = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+
+    sector / 6.0
+}
+
+// Fully saturated color of a hue
+fn hue_to_rgb(hue: f32) -> (f32, f32, f32) {
+    let channel = |n: f32| {
+        let k = (n + hue * 6.0).rem_euclid(6.0);
+        1.0 - k.min(4.0 - k).clamp(0.0, 1.0)
+    };
+
+    (channel(5.0), channel(3.0), channel(1.0))
+}
+
+fn from_hue_chroma(hue: f32, chroma: f32, min: f32) -> (f32, f32, f32) {
+    let (r, g, b) = hue_to_rgb(hue);
+
+    (min + chroma * r, min + chroma * g, min + chroma * b)
+}
+
+// Trilinear blend of the RYB corners
+fn ryb_to_rgb((r, y, b): (f32, f32, f32)) -> (f32, f32, f32) {
+    let mut rgb = (0.0, 0.0, 0.0);
+
+    for (index, corner) in RYB_CORNERS.iter().enumerate() {
+        let weight = |t: f32, bit: usize| if index & bit != 0 { t } else { 1.0 - t };
+        let w = weight(r, 1) * weight(y, 2) * weight(b, 4);
+
+        rgb.0 += corner.0 * w;
+        rgb.1 += corner.1 * w;
+        rgb.2 += corner.2 * w;
+    }
+
+    rgb
+}
+
+// Inverts ryb_to_rgb with Newton's method, colors outside the RYB gamut end up near the closest
+// one it reaches
+fn rgb_to_ryb(rgb: (f32, f32, f32)) -> (f32, f32, f32) {
+    let target = Vector3::new(rgb.0, rgb.1, rgb.2);
+    let eval = |c: &Vector3<f32>| {
+        let (r, g, b) = ryb_to_rgb((c.x, c.y, c.z));
+        Vector3::new(r, g, b)
+    };
+
+    let mut ryb = Vector3::new(0.5, 0.5, 0.5);
+    for _ in 0..RYB_INVERSE_ITERATIONS {
+        let residual = eval(&ryb) - target;
+        if residual.norm() < 1e-4 {
+            break;
+        }
+
+        let step = 1e-3;
+        let mut jacobian = Matrix3::zeros();
+        for axis in 0..3 {
+            let mut nudged = ryb;
+            nudged[axis] += step;
+            jacobian.set_column(axis, &((eval(&nudged) - eval(&ryb)) / step));
+        }
+
+        match jacobian.try_inverse() {
+            Some(inverse) => ryb = (ryb - inverse * residual).map(|c| c.clamp(0.0, 1.0)),
+            None => break,
+        }
+    }
+
+    (ryb.x, ryb.y, ryb.z)
+}
+
+fn push_vertex(vertices: &mut Vec<f32>, position: Vec3, (r, g, b): (f32, f32, f32)) {
+    vertices.extend_from_slice(&[position.x, position.y, position.z, r, g, b]);
+}
+
+// Point on a circle of the round solids, hue turning counterclockwise seen from above
+fn around(hue: f32, radius: f32, y: f32) -> Vec3 {
+    let angle = hue * TAU;
+
+    Vec3::new(radius * angle.cos(), y, -radius * angle.sin())
+}
+
+impl Solid {
+    pub fn name(self) -> &'static str {
+        match self {
+            Solid::RgbCube => "RGB cube",
+            Solid::CmyCube => "CMY cube",
+            Solid::RybCube => "RYB cube",
+            Solid::HsvHexcone => "HSV hexcone",
+            Solid::HsvCylinder => "HSV cylinder",
+            Solid::HslBicone => "HSL double cone",
+            Solid::LabHull => "sRGB in CIELAB",
+            Solid::OklabHull => "sRGB in OKLab",
+        }
+    }
+
+    pub fn is_cube(self) -> bool {
+        matches!(self, Solid::RgbCube | Solid::CmyCube | Solid::RybCube)
+    }
+
+    pub fn is_hull(self) -> bool {
+        matches!(self, Solid::LabHull | Solid::OklabHull)
+    }
+
+    // Color at a corner of a cube solid, its coordinates each 0..1
+    pub fn cube_color(self, (u, v, w): (f32, f32, f32)) -> (f32, f32, f32) {
+        match self {
+            Solid::CmyCube => (1.0 - u, 1.0 - v, 1.0 - w),
+            Solid::RybCube => ryb_to_rgb((u, v, w)),
+            _ => (u, v, w),
+        }
+    }
+
+    // Where a color sits in the solid, in the same -0.5..0.5 box as the RGB cube. The hulls also
+    // place sRGB values outside 0..1, for wider gamuts.
+    pub fn position(self, rgb: (f32, f32, f32)) -> Vec3 {
+        let (r, g, b) = rgb;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+
+        match self {
+            Solid::RgbCube => rgb_to_cube_position(rgb),
+            Solid::CmyCube => rgb_to_cube_position((1.0 - r, 1.0 - g, 1.0 - b)),
+            Solid::RybCube => rgb_to_cube_position(rgb_to_ryb(rgb)),
+            Solid::HsvHexcone => {
+                // Chroma projected along the gray axis, hexagonal with the primaries at its corners
+                let (alpha, beta) = (r - (g + b) / 2.0, (g - b) * 3f32.sqrt() / 2.0);
+                Vec3::new(alpha / 2.0, max - 0.5, -beta / 2.0)
+            }
+            Solid::HsvCylinder => {
+                let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+                around(hue(rgb), saturation / 2.0, max - 0.5)
+            }
+            Solid::HslBicone => around(hue(rgb), (max - min) / 2.0, (max + min) / 2.0 - 0.5),
+            Solid::LabHull => {
+                let (l, a, b) = rgb_to_lab(rgb);
+                Vec3::new(a * LAB_CHROMA_SCALE, l / 100.0 - 0.5, -b * LAB_CHROMA_SCALE)
+            }
+            Solid::OklabHull => {
+                let (l, a, b) = rgb_to_oklab(rgb);
+                Vec3::new(a * OKLAB_CHROMA_SCALE, l - 0.5, -b * OKLAB_CHROMA_SCALE)
+            }
+        }
+    }
+
+    // Color at a position inside the solid, None outside it. The cross-sections sample it through
+    // a ColorVolume.
+    pub fn color_at(self, p: Vec3) -> Option<(f32, f32, f32)> {
+        let inside = |t: f32| (-SURFACE_TOLERANCE..=1.0 + SURFACE_TOLERANCE).contains(&t);
+        let within = |t: f32, limit: f32| t <= limit + SURFACE_TOLERANCE;
+
+        match self {
+            Solid::RgbCube | Solid::CmyCube | Solid::RybCube => {
+                let coords = (p.y + 0.5, p.z + 0.5, p.x + 0.5);
+                (inside(coords.0) && inside(coords.1) && inside(coords.2))
+                    .then(|| self.cube_color(coords))
+            }
+            Solid::HsvHexcone => {
+                let value = p.y + 0.5;
+                let (alpha, beta) = (p.x * 2.0, -p.z * 2.0);
+                let d = [
+                    alpha * 2.0 / 3.0,
+                    -alpha / 3.0 + beta / 3f32.sqrt(),
+                    -alpha / 3.0 - beta / 3f32.sqrt(),
+                ];
+                let max = d[0].max(d[1]).max(d[2]);
+                let chroma = max - d[0].min(d[1]).min(d[2]);
+
+                (inside(value) && within(chroma, value)).then(|| {
+                    let gray = value - max;
+                    (d[0] + gray, d[1] + gray, d[2] + gray)
+                })
+            }
+            Solid::HsvCylinder => {
+                let value = p.y + 0.5;
+                let saturation = 2.0 * (p.x * p.x + p.z * p.z).sqrt();
+                let hue = (-p.z).atan2(p.x) / TAU;
+                let chroma = value * saturation;
+
+                (inside(value) && within(saturation, 1.0))
+                    .then(|| from_hue_chroma(hue, chroma, value - chroma))
+            }
+            Solid::HslBicone => {
+                let lightness = p.y + 0.5;
+                let chroma = 2.0 * (p.x * p.x + p.z * p.z).sqrt();
+                let hue = (-p.z).atan2(p.x) / TAU;
+
+                (inside(lightness) && within(chroma, 1.0 - (2.0 * lightness - 1.0).abs()))
+                    .then(|| from_hue_chroma(hue, chroma, lightness - chroma / 2.0))
+            }
+            Solid::LabHull | Solid::OklabHull => {
+                let rgb = if self == Solid::LabHull {
+                    let scale = 1.0 / LAB_CHROMA_SCALE;
+                    lab_to_rgb(((p.y + 0.5) * 100.0, p.x * scale, -p.z * scale))
+                } else {
+                    let scale = 1.0 / OKLAB_CHROMA_SCALE;
+                    oklab_to_rgb((p.y + 0.5, p.x * scale, -p.z * scale))
+                };
+
+                (inside(rgb.0) && inside(rgb.1) && inside(rgb.2)).then(|| clamp_rgb(rgb))
+            }
+        }
+    }
+
+    // Eight named colors to label, the corners for the cubes
+    pub fn landmarks(self) -> [(&'static str, Vec3); 8] {
+        if self == Solid::RybCube {
+            let corner = |index: usize| {
+                let bit = |b: usize| (index & b != 0) as u32 as f32;
+                rgb_to_cube_position((bit(1), bit(2), bit(4)))
+            };
+            return [0, 1, 2, 3, 4, 5, 6, 7].map(|index| (RYB_CORNER_NAMES[index], corner(index)));
+        }
+
+        RGB_CORNERS.map(|(name, rgb)| (name, self.position(rgb)))
+    }
+
+    // Interleaved position and color triangles with their indices
+    pub fn mesh(self) -> (Vec<f32>, Vec<u32>) {
+        match self {
+            Solid::RgbCube => (CUBE_VERTICES.to_vec(), CUBE_INDICES.to_vec()),
+            Solid::CmyCube => {
+                // The RGB cube turned inside out through its center
+                let mut vertices = CUBE_VERTICES.to_vec();
+                for vertex in vertices.chunks_exact_mut(6) {
+                    for c in &mut vertex[..3] {
+                        *c = -*c;
+                    }
+                }
+                (vertices, CUBE_INDICES.to_vec())
+            }
+            Solid::RybCube => subdivided_cube_mesh(RYB_SUBDIVISIONS, |coords| {
+                (rgb_to_cube_position(coords), ryb_to_rgb(coords))
+            }),
+            Solid::HsvHexcone => {
+                let mut vertices = Vec::new();
+                push_vertex(&mut vertices, Vec3::new(0.0, -0.5, 0.0), (0.0, 0.0, 0.0));
+                push_vertex(&mut vertices, Vec3::new(0.0, 0.5, 0.0), (1.0, 1.0, 1.0));
+                for sector in 0..6 {
+                    let rgb = hue_to_rgb(sector as f32 / 6.0);
+                    push_vertex(&mut vertices, self.position(rgb), rgb);
+                }
+
+                let mut indices = Vec::new();
+                for sector in 0..6 {
+                    let (a, b) = (2 + sector, 2 + (sector + 1) % 6);
+                    indices.extend_from_slice(&[0, a, b, 1, a, b]);
+                }
+                (vertices, indices)
+            }
+            Solid::HsvCylinder | Solid::HslBicone => round_mesh(self),
+            Solid::LabHull | Solid::OklabHull => {
+                subdivided_cube_mesh(HULL_SUBDIVISIONS, |rgb| (self.position(rgb), rgb))
+            }
+        }
+    }
+
+    // Moves interleaved position and color points to where their colors sit in this solid
+    pub fn place_points(self, vertices: &[f32]) -> Vec<f32> {
+        let mut placed = vertices.to_vec();
+        if self == Solid::RgbCube {
+            return placed;
+        }
+
+        for vertex in placed.chunks_exact_mut(6) {
+            let position = self.position((vertex[3], vertex[4], vertex[5]));
+            vertex[..3].copy_from_slice(position.as_slice());
+        }
+        placed
+    }
+}
+
+// A solid's color_at sampled on a grid, the one place the cross-sections get their colors from.
+// WebGL uploads it as a 3D texture and the CPU samples it the same way, with linear filtering
+// clamped at the edges and everything under half coverage left out.
+pub struct ColorVolume {
+    solid: Solid,
+    // RGBA, x fastest then y then z, transparent black outside the solid
+    texels: Vec<u8>,
+}
+
+impl ColorVolume {
+    pub fn new(solid: Solid) -> Self {
+        let size = VOLUME_SIZE as usize;
+        let mut texels = Vec::with_capacity(size * size * size * 4);
+
+        let coordinate =
+            |index: usize| VOLUME_EXTENT * (2.0 * (index as f32 + 0.5) / VOLUME_SIZE as f32 - 1.0);
+        for z in 0..size {
+            for y in 0..size {
+                for x in 0..size {
+                    let position = Vec3::new(coordinate(x), coordinate(y), coordinate(z));
+                    let texel = match solid.color_at(position) {
+                        Some((r, g, b)) => [r, g, b, 1.0].map(|c| (c * 255.0).round() as u8),
+                        None => [0; 4],
+                    };
+                    texels.extend_from_slice(&texel);
+                }
+            }
+        }
+
+        Self { solid, texels }
+    }
+
+    pub fn solid(&self) -> Solid {
+        self.solid
+    }
+
+    pub fn texels(&self) -> &[u8] {
+        &self.texels
+    }
+
+    // Color at a position like the cross-section shader's texture lookup, None outside the solid
+    pub fn sample(&self, p: Vec3) -> Option<(f32, f32, f32)> {
+        let coords = p / (2.0 * VOLUME_EXTENT) + Vec3::new(0.5, 0.5, 0.5);
+        if coords.iter().any(|c| !(0.0..=1.0).contains(c)) {
+            return None;
+        }
+
+        // Neighbouring texels and the weight of the second one along each axis
+        let last = VOLUME_SIZE as f32 - 1.0;
+        let axis = |c: f32| {
+            let t = (c * VOLUME_SIZE as f32 - 0.5).clamp(0.0, last);
+            let low = t.floor();
+            (low as usize, (low + 1.0).min(last) as usize, t - low)
+        };
+        let (x, y, z) = (axis(coords.x), axis(coords.y), axis(coords.z));
+
+        let size = VOLUME_SIZE as usize;
+        let mut rgba = [0.0; 4];
+        for (xi, wx) in [(x.0, 1.0 - x.2), (x.1, x.2)] {
+            for (yi, wy) in [(y.0, 1.0 - y.2), (y.1, y.2)] {
+                for (zi, wz) in [(z.0, 1.0 - z.2), (z.1, z.2)] {
+                    let index = ((zi * size + yi) * size + xi) * 4;
+                    for (channel, value) in rgba.iter_mut().enumerate() {
+                        *value += self.texels[index + channel] as f32 / 255.0 * wx * wy * wz;
+                    }
+                }
+            }
+        }
+
+        // Outside texels are transparent black, so dividing by coverage undoes their darkening
+        let [r, g, b, a] = rgba;
+        (a >= 0.5).then(|| (r / a, g / a, b / a))
+    }
+}
+
+// Point on a face of the unit cube. Each face holds one coordinate at 0 or 1 and runs the other
+// two over s and t.
+pub fn cube_face_point(axis: usize, side: f32, s: f32, t: f32) -> (f32, f32, f32) {
+    let mut coords = [side; 3];
+    coords[(axis + 1) % 3] = s;
+    coords[(axis + 2) % 3] = t;
+
+    (coords[0], coords[1], coords[2])
+}
+
+// Every face split into a grid, each vertex placed and colored exactly by vertex
+fn subdivided_cube_mesh(
+    n: u32,
+    vertex: impl Fn((f32, f32, f32)) -> (Vec3, (f32, f32, f32)),
+) -> (Vec<f32>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for axis in 0..3 {
+        for side in [0.0, 1.0] {
+            let first = vertices.len() as u32 / 6;
+
+            for i in 0..=n {
+                for j in 0..=n {
+                    let (s, t) = (i as f32 / n as f32, j as f32 / n as f32);
+                    let (position, rgb) = vertex(cube_face_point(axis, side, s, t));
+
+                    push_vertex(&mut vertices, position, rgb);
+                }
+            }
+
+            for i in 0..n {
+                for j in 0..n {
+                    let corner = first + i * (n + 1) + j;
+                    let (right, up) = (corner + n + 1, corner + 1);
+                    indices.extend_from_slice(&[corner, right, right + 1, right + 1, up, corner]);
+                }
+            }
+        }
+    }
+
+    (vertices, indices)
+}
+
+// HSV cylinder or HSL double cone, rings of hues between black and white
+fn round_mesh(solid: Solid) -> (Vec<f32>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    push_vertex(&mut vertices, Vec3::new(0.0, -0.5, 0.0), (0.0, 0.0, 0.0));
+    push_vertex(&mut vertices, Vec3::new(0.0, 0.5, 0.0), (1.0, 1.0, 1.0));
+
+    // The cylinder has a black bottom rim and a saturated top one, the double cone a saturated
+    // rim around its middle
+    let rings: &[(f32, bool)] = match solid {
+        Solid::HsvCylinder => &[(-0.5, false), (0.5, true)],
+        _ => &[(0.0, true)],
+    };
+    for (y, saturated) in rings {
+        for segment in 0..SEGMENTS {
+            let hue = segment as f32 / SEGMENTS as f32;
+            let rgb = if *saturated {
+                hue_to_rgb(hue)
+            } else {
+                (0.0, 0.0, 0.0)
+            };
+            push_vertex(&mut vertices, around(hue, 0.5, *y), rgb);
+        }
+    }
+
+    let ring = |ring: u32, segment: u32| 2 + ring * SEGMENTS + segment % SEGMENTS;
+    let last = rings.len() as u32 - 1;
+    let mut indices = Vec::new();
+    for segment in 0..SEGMENTS {
+        let next = segment + 1;
+        indices.extend_from_slice(&[0, ring(0, segment), ring(0, next)]);
+        indices.extend_from_slice(&[1, ring(last, segment), ring(last, next)]);
+
+        for r in 0..last {
+            let (a, b) = (ring(r, segment), ring(r, next));
+            let (c, d) = (ring(r + 1, segment), ring(r + 1, next));
+            indices.extend_from_slice(&[a, b, d, d, c, a]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: (f32, f32, f32), actual: (f32, f32, f32), solid: Solid) {
+        let error = (expected.0 - actual.0)
+            .abs()
+            .max((expected.1 - actual.1).abs())
+            .max((expected.2 - actual.2).abs());
+        assert!(
+            error < 1e-3,
+            "{} placed {:?} where it shows {:?}",
+            solid.name(),
+            expected,
+            actual
+        );
+    }
+
+    // A point placed for a color has to land where the solid shows that color
+    #[test]
+    fn positions_show_their_colors() {
+        let steps = (0..=4).map(|step| step as f32 / 4.0);
+
+        for solid in SOLIDS {
+            for r in steps.clone() {
+                for g in steps.clone() {
+                    for b in steps.clone() {
+                        // Only colors inside the RYB gamut have a place in its cube
+                        let rgb = match solid {
+                            Solid::RybCube => ryb_to_rgb((r, g, b)),
+                            _ => (r, g, b),
+                        };
+
+                        let shown = solid.color_at(solid.position(rgb)).unwrap_or_else(|| {
+                            panic!("{} placed {:?} outside itself", solid.name(), rgb)
+                        });
+                        assert_close(rgb, shown, solid);
+                    }
+                }
+            }
+        }
+    }
+
+    // The volume has to show what color_at does away from the solid's surface
+    #[test]
+    fn volume_samples_match_color_at() {
+        for solid in SOLIDS {
+            let volume = ColorVolume::new(solid);
+            let steps = (0..=8).map(|step| step as f32 / 8.0 - 0.5);
+
+            for x in steps.clone() {
+                for y in steps.clone() {
+                    for z in steps.clone() {
+                        let p = Vec3::new(x, y, z);
+                        let inner = |offset: Vec3| solid.color_at(p + offset).is_some();
+                        let well_inside = [-0.03, 0.03].iter().all(|d| {
+                            inner(Vec3::new(*d, 0.0, 0.0))
+                                && inner(Vec3::new(0.0, *d, 0.0))
+                                && inner(Vec3::new(0.0, 0.0, *d))
+                        });
+                        if !well_inside {
+                            continue;
+                        }
+
+                        let expected = solid.color_at(p).unwrap();
+                        let sampled = volume.sample(p).unwrap_or_else(|| {
+                            panic!("{} volume has nothing at {:?}", solid.name(), p)
+                        });
+                        let error = (expected.0 - sampled.0)
+                            .abs()
+                            .max((expected.1 - sampled.1).abs())
+                            .max((expected.2 - sampled.2).abs());
+                        assert!(error < 0.03, "{} at {:?}", solid.name(), p);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use gloo_events::EventListener;
+use web_sys::{FileReader, HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::color::{
+    clamp_rgb, cmyk_to_rgb, delta_e_2000, lab_to_rgb, rgb_to_cmyk, rgb_to_lab, rgb_to_str,
+};
+use crate::color_picker::PickedColor;
+
+// Closest inks shown by default, and at most
+const NEAREST_COUNT: usize = 5;
+const MAX_NEAREST_COUNT: usize = 32;
+
+// Libraries are CSV, one ink per line, either by CIELAB (D50) or by CMYK percentages. Fields can
+// be quoted to hold commas, with "" for a quote inside them:
+//
+//     # comment
+//     Warm Red,lab,53.6,68.4,59.6
+//     "Blue, process",cmyk,100,13,1,2
+const FORMAT_HINT: &str = "CSV, one ink per line: name,lab,L,a,b or name,cmyk,C,M,Y,K in \
+    percent. Quote names that contain commas, lines starting with # are comments.";
+const BUILTIN_LIBRARY: &str = "\
+# ISO Coated v2 solids and overprints
+Paper White,lab,95,0,-2
+Process Cyan,lab,55,-37,-50
+Process Magenta,lab,48,74,-3
+Process Yellow,lab,89,-5,93
+Process Black,lab,16,0,0
+Red (M+Y),lab,47,68,48
+Green (C+Y),lab,50,-65,27
+Blue (C+M),lab,24,22,-46
+";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum InkDefinition {
+    Lab((f32, f32, f32)),
+    Cmyk((f32, f32, f32, f32)),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct SpotInk {
+    pub name: String,
+    pub definition: InkDefinition,
+}
+
+impl SpotInk {
+    pub fn lab(&self) -> (f32, f32, f32) {
+        match self.definition {
+            InkDefinition::Lab(lab) => lab,
+            InkDefinition::Cmyk(cmyk) => rgb_to_lab(cmyk_to_rgb(cmyk)),
+        }
+    }
+
+    pub fn picked_color(&self) -> PickedColor {
+        match self.definition {
+            InkDefinition::Lab(lab) => {
+                let rgb = clamp_rgb(lab_to_rgb(lab));
+
+                PickedColor {
+                    rgb,
+                    cmyk: rgb_to_cmyk(rgb),
+                }
+            }
+            InkDefinition::Cmyk(cmyk) => PickedColor {
+                rgb: cmyk_to_rgb(cmyk),
+                cmyk,
+            },
+        }
+    }
+}
+
+// Splits a CSV record into its fields, unquoted ones are trimmed
+fn split_record(line: &str) -> Result<Vec<String>, &'static str> {
+    let finish = |field: &str, quoted: bool| {
+        if quoted {
+            field.to_string()
+        } else {
+            field.trim().to_string()
+        }
+    };
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    // Whether the field started with a quote, and whether it's still open
+    let (mut quoted, mut open) = (false, false);
+
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if open => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    open = false;
+                }
+            }
+            '"' if !quoted && field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+                open = true;
+            }
+            ',' if !open => {
+                fields.push(finish(&field, quoted));
+                field.clear();
+                quoted = false;
+            }
+            _ if open => field.push(c),
+            _ if quoted && !c.is_whitespace() => return Err("text after a closing quote"),
+            _ if quoted => {}
+            '"' => return Err("a quote inside an unquoted field"),
+            _ => field.push(c),
+        }
+    }
+
+    if open {
+        return Err("a quote that's never closed");
+    }
+    fields.push(finish(&field, quoted));
+
+    Ok(fields)
+}
+
+fn parse_ink(line: &str) -> Result<SpotInk, String> {
+    let fields = split_record(line)?;
+    let name = match fields.first() {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => return Err("missing ink name".to_string()),
+    };
+
+    let values = fields
+        .iter()
+        .skip(2)
+        .map(|value| match value.parse::<f32>() {
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => Err(format!("\"{}\" isn't a number", value)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let percent = |value: f32| (0.0..=100.0).contains(&value);
+
+    let kind = fields.get(1).map(|kind| kind.to_ascii_lowercase());
+    let definition = match (kind.as_deref(), values.as_slice()) {
+        (Some("lab"), [l, a, b]) if percent(*l) => InkDefinition::Lab((*l, *a, *b)),
+        (Some("lab"), [_, _, _]) => return Err("L must be between 0 and 100".to_string()),
+        (Some("cmyk"), [c, m, y, k]) if [c, m, y, k].iter().all(|value| percent(**value)) => {
+            InkDefinition::Cmyk((*c / 100.0, *m / 100.0, *y / 100.0, *k / 100.0))
+        }
+        (Some("cmyk"), [_, _, _, _]) => {
+            return Err("CMYK percentages must be between 0 and 100".to_string())
+        }
+        _ => return Err("expected name,lab,L,a,b or name,cmyk,C,M,Y,K".to_string()),
+    };
+
+    Ok(SpotInk { name, definition })
+}
+
+// A file with any malformed line is rejected as a whole, naming the line
+pub fn parse_library(text: &str) -> Result<Vec<SpotInk>, String> {
+    let mut inks = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let ink =
+            parse_ink(line).map_err(|problem| format!("Line {}: {}.", number + 1, problem))?;
+        inks.push(ink);
+    }
+
+    Ok(inks)
+}
+
+// Indices of the closest inks and their distance by CIEDE2000
+pub fn nearest_inks(inks: &[SpotInk], rgb: (f32, f32, f32), count: usize) -> Vec<(usize, f32)> {
+    let lab = rgb_to_lab(rgb);
+    let mut distances: Vec<_> = inks
+        .iter()
+        .enumerate()
+        .map(|(index, ink)| (index, delta_e_2000(lab, ink.lab())))
+        .collect();
+
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+    distances.truncate(count);
+    distances
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub rgb: (f32, f32, f32),
+    pub onpick: Callback<PickedColor>,
+}
+
+pub enum Msg {
+    FileSelected(Event),
+    LibraryRead(String),
+    InkPicked(usize),
+    CountChanged(usize),
+}
+
+pub struct SpotLibraryPanel {
+    inks: Vec<SpotInk>,
+    // The built-in inks come first, anything after them was imported
+    builtin: usize,
+    // How many of the closest inks to show
+    count: usize,
+    error: Option<String>,
+    read_listener: Option<EventListener>,
+}
+
+impl Component for SpotLibraryPanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        let inks = parse_library(BUILTIN_LIBRARY).expect("Built-in spot library is invalid.");
+
+        Self {
+            builtin: inks.len(),
+            inks,
+            count: NEAREST_COUNT,
+            error: None,
+            read_listener: None,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let nearest = nearest_inks(&self.inks, ctx.props().rgb, self.count);
+        let nearest = nearest.into_iter().map(|(index, distance)| {
+            let ink = &self.inks[index];
+            let background = format!(
+                "background-color: {}; width: 48px; height: 48px; cursor: pointer;",
+                rgb_to_str(ink.picked_color().rgb)
+            );
+
+            html! {
+                <div class="col-auto text-center">
+                    <div
+                        style={background}
+                        onclick={ctx.link().callback(move |_| Msg::InkPicked(index))}>
+                    </div>
+                    <div><small>{ink.name.clone()}</small></div>
+                    <small>{format!("ΔE00 {:.2}", distance)}</small>
+                </div>
+            }
+        });
+
+        let options = self.inks.iter().enumerate().map(|(index, ink)| {
+            html! {
+                <option value={index.to_string()}>{ink.name.clone()}</option>
+            }
+        });
+
+        let on_ink_select = ctx.link().batch_callback(|event: Event| {
+            let select = event.target_unchecked_into::<HtmlSelectElement>().value();
+
+            select.parse().ok().map(Msg::InkPicked)
+        });
+
+        let on_count_change = ctx.link().callback(|event: InputEvent| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+
+            Msg::CountChanged(input.value_as_number().clamp(1.0, MAX_NEAREST_COUNT as f64) as usize)
+        });
+
+        html! {
+            <div class="container">
+                <div class="row">
+                    <div class="col">
+                        <label>{"Spot inks: "}</label>
+                        <select onchange={on_ink_select}>
+                            <option value="" selected=true disabled=true>{"Pick an ink"}</option>
+                            { for options }
+                        </select>
+                        <label>{" Closest: "}</label>
+                        <input
+                            type="number"
+                            min="1"
+                            max={MAX_NEAREST_COUNT.to_string()}
+                            value={self.count.to_string()}
+                            oninput={on_count_change} />
+                        <input
+                            type="file"
+                            accept=".csv,.txt"
+                            onchange={ctx.link().callback(Msg::FileSelected)} />
+                        if let Some(error) = &self.error {
+                            <span class="text-danger">{error.clone()}</span>
+                        }
+                        <div><small class="text-muted">{FORMAT_HINT}</small></div>
+                    </div>
+                </div>
+                <div class="row">
+                    { for nearest }
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::FileSelected(event) => {
+                let input = event.target_unchecked_into::<HtmlInputElement>();
+                let file = match input.files().and_then(|files| files.get(0)) {
+                    Some(file) => file,
+                    None => return false,
+                };
+
+                let reader = FileReader::new().expect("Couldn't create file reader.");
+                let callback = ctx.link().callback(Msg::LibraryRead);
+                self.read_listener = Some(EventListener::once(&reader, "load", {
+                    let reader = reader.clone();
+                    move |_| {
+                        let text = reader
+                            .result()
+                            .ok()
+                            .and_then(|result| result.as_string())
+                            .unwrap_or_default();
+
+                        callback.emit(text)
+                    }
+                }));
+
+                reader
+                    .read_as_text(&file)
+                    .expect("Couldn't read spot library.");
+
+                false
+            }
+            Msg::LibraryRead(text) => {
+                self.read_listener = None;
+
+                match parse_library(&text) {
+                    Ok(inks) => {
+                        // A new library replaces the previously imported one
+                        self.inks.truncate(self.builtin);
+                        self.inks.extend(inks);
+                        self.error = None;
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+
+                true
+            }
+            Msg::InkPicked(index) => {
+                if let Some(ink) = self.inks.get(index) {
+                    ctx.props().onpick.emit(ink.picked_color());
+                }
+
+                false
+            }
+            Msg::CountChanged(count) => {
+                self.count = count;
+
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_names_and_both_definitions() {
+        let inks = parse_library(
+            "# comment\n\
+             \n\
+             Warm Red, lab, 53.6, 68.4, 59.6\n\
+             \"Blue, \"\"process\"\"\",CMYK,100,13,1,2\n",
+        )
+        .unwrap();
+
+        assert_eq!(inks.len(), 2);
+        assert_eq!(inks[0].name, "Warm Red");
+        assert!(inks[0].definition == InkDefinition::Lab((53.6, 68.4, 59.6)));
+        assert_eq!(inks[1].name, "Blue, \"process\"");
+        assert!(inks[1].definition == InkDefinition::Cmyk((1.0, 0.13, 0.01, 0.02)));
+    }
+
+    #[test]
+    fn rejects_malformed_rows_by_line() {
+        for (text, error) in [
+            ("Red,lab,50,60\n", "Line 1: expected"),
+            (
+                "# ok\nRed,lab,50,sixty,0\n",
+                "Line 2: \"sixty\" isn't a number.",
+            ),
+            ("Red,cmyk,0,120,0,0\n", "Line 1: CMYK percentages"),
+            ("Red,lab,150,0,0\n", "Line 1: L must be"),
+            (",lab,50,0,0\n", "Line 1: missing ink name."),
+            ("\"Red,lab,50,0,0\n", "Line 1: a quote that's never closed."),
+            (
+                "Re\"d,lab,50,0,0\n",
+                "Line 1: a quote inside an unquoted field.",
+            ),
+            (
+                "\"Red\" ish,lab,50,0,0\n",
+                "Line 1: text after a closing quote.",
+            ),
+        ] {
+            let result = parse_library(text).map(|inks| inks.len());
+            assert!(
+                matches!(&result, Err(message) if message.starts_with(error)),
+                "{:?} gave {:?}",
+                text,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn builtin_library_parses() {
+        assert_eq!(parse_library(BUILTIN_LIBRARY).unwrap().len(), 8);
+    }
+}
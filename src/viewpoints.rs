@@ -0,0 +1,64 @@
+use nalgebra::{UnitQuaternion, Vector3};
+
+pub struct Viewpoint {
+    pub name: &'static str,
+    // Direction from the cube center toward the viewer and the direction shown as up, both in
+    // cube coordinates where x is blue, y is red and z is green
+    toward: [f32; 3],
+    up: [f32; 3],
+}
+
+// Selected with the number keys in order
+pub const VIEWPOINTS: [Viewpoint; 8] = [
+    Viewpoint {
+        name: "Gray axis",
+        toward: [1.0, 1.0, 1.0],
+        up: [0.0, 1.0, 0.0],
+    },
+    Viewpoint {
+        name: "R=0",
+        toward: [0.0, -1.0, 0.0],
+        up: [0.0, 0.0, 1.0],
+    },
+    Viewpoint {
+        name: "R=1",
+        toward: [0.0, 1.0, 0.0],
+        up: [0.0, 0.0, 1.0],
+    },
+    Viewpoint {
+        name: "G=0",
+        toward: [0.0, 0.0, -1.0],
+        up: [0.0, 1.0, 0.0],
+    },
+    Viewpoint {
+        name: "G=1",
+        toward: [0.0, 0.0, 1.0],
+        up: [0.0, 1.0, 0.0],
+    },
+    Viewpoint {
+        name: "B=0",
+        toward: [-1.0, 0.0, 0.0],
+        up: [0.0, 1.0, 0.0],
+    },
+    Viewpoint {
+        name: "B=1",
+        toward: [1.0, 0.0, 0.0],
+        up: [0.0, 1.0, 0.0],
+    },
+    // Yellow corner in front with the red axis up
+    Viewpoint {
+        name: "Isometric",
+        toward: [-1.0, 1.0, 1.0],
+        up: [0.0, 1.0, 0.0],
+    },
+];
+
+impl Viewpoint {
+    // Model rotation that presents this side of the cube to a camera looking down -z
+    pub fn orientation(&self) -> UnitQuaternion<f32> {
+        let toward = Vector3::from(self.toward);
+        let up = Vector3::from(self.up);
+
+        UnitQuaternion::look_at_rh(&-toward, &up)
+    }
+}
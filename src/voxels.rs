@@ -0,0 +1,53 @@
+use crate::cube::rgb_to_cube_position;
+use crate::solids::Solid;
+
+pub const MIN_RESOLUTION: u32 = 2;
+pub const MAX_RESOLUTION: u32 = 32;
+pub const MAX_SPACING: f32 = 1.0;
+
+// How the cube is split into voxels
+#[derive(Clone, Copy, PartialEq)]
+pub struct VoxelOptions {
+    // Voxels along each edge
+    pub resolution: u32,
+    // Gap between voxels as a fraction of their size
+    pub spacing: f32,
+    // Leaves out the voxels in front of the slice plane
+    pub cutaway: bool,
+}
+
+impl Default for VoxelOptions {
+    fn default() -> Self {
+        Self {
+            resolution: 8,
+            spacing: 0.0,
+            cutaway: false,
+        }
+    }
+}
+
+// Center and color of every voxel of a cube solid, six floats each. A voxel shows the color at the
+// middle of its cell, so neighbours differ by one quantization step.
+pub fn voxel_instances(resolution: u32, solid: Solid) -> Vec<f32> {
+    let step = 1.0 / resolution as f32;
+    let mut instances = Vec::with_capacity((resolution.pow(3) * 6) as usize);
+
+    for u in 0..resolution {
+        for v in 0..resolution {
+            for w in 0..resolution {
+                let coords = (
+                    (u as f32 + 0.5) * step,
+                    (v as f32 + 0.5) * step,
+                    (w as f32 + 0.5) * step,
+                );
+                let center = rgb_to_cube_position(coords);
+                let color = solid.cube_color(coords);
+
+                instances
+                    .extend_from_slice(&[center.x, center.y, center.z, color.0, color.1, color.2]);
+            }
+        }
+    }
+
+    instances
+}